@@ -0,0 +1,63 @@
+//! Compares `--print` tail reading across note file sizes.
+//!
+//! Run with `cargo bench --bench tail_read`. The backwards reader should take
+//! roughly the same time regardless of file size; the forward reader grows
+//! linearly with it.
+
+use std::{
+    fs::File,
+    hint::black_box,
+    io::{BufReader, BufWriter},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use nt::notes::{
+    append_note_line_to_writer, collect_last_n_lines_from_file, collect_last_n_lines_from_reader,
+};
+
+const SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+const COUNT: usize = 10;
+const ITERATIONS: u32 = 20;
+
+fn write_notes(path: &Path, notes: usize) {
+    let mut writer = BufWriter::new(File::create(path).expect("create note file"));
+    for i in 0..notes {
+        let text = if i % 7 == 0 {
+            format!("note {i} #bench\nwith a second line")
+        } else {
+            format!("note {i} #bench")
+        };
+        append_note_line_to_writer(&mut writer, "2025-01-01 12:00", &text).expect("write note");
+    }
+}
+
+fn time_per_call(mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    println!(
+        "{:>10} {:>12} {:>14} {:>14}",
+        "notes", "bytes", "backwards", "forward"
+    );
+    for notes in SIZES {
+        let path = dir.path().join(format!("notes-{notes}.txt"));
+        write_notes(&path, notes);
+        let bytes = std::fs::metadata(&path).expect("stat note file").len();
+        let backwards = time_per_call(|| {
+            black_box(collect_last_n_lines_from_file(&path, COUNT).expect("tail"));
+        });
+        let forward = time_per_call(|| {
+            let reader = BufReader::new(File::open(&path).expect("open note file"));
+            black_box(collect_last_n_lines_from_reader(reader, COUNT).expect("tail"));
+        });
+        println!("{notes:>10} {bytes:>12} {backwards:>14.2?} {forward:>14.2?}");
+    }
+}
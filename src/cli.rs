@@ -1,32 +1,462 @@
-use clap::{ArgAction, Parser, error::ErrorKind};
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args, Parser, Subcommand, error::ErrorKind};
+
+use crate::config::config_file_from_environment;
+use crate::export::ExportFormat;
+use crate::notes::StorageFormat;
+use crate::output::OutputArgs;
+use crate::query::FilterArgs;
+use crate::search::SearchModeArgs;
+use crate::selector::NoteSelector;
 
 #[derive(Parser, Debug)]
 #[command(name = "nt", about = "Simple timestamped note taker", version, author)]
 pub struct Cli {
-    #[arg(short='p', long="print", num_args=0..=1, value_name="N", default_missing_value="10")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(
+        short = 'p',
+        long = "print",
+        num_args = 0..=1,
+        value_name = "N",
+        help = "print the last N notes (default 10, or every note in a --since/--until/--on range)"
+    )]
     pub print: Option<Option<usize>>,
-    #[arg(long = "config-path", help = "print the default config file path and exit", action = ArgAction::SetTrue)]
+    #[arg(
+        short = 's',
+        long = "search",
+        value_name = "PATTERN",
+        conflicts_with = "print",
+        help = "print notes matching PATTERN (same as `nt search`)"
+    )]
+    pub search: Option<String>,
+    #[command(flatten)]
+    pub search_mode: SearchModeArgs,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[arg(long = "config-path", help = "print the config file path in use and exit", action = ArgAction::SetTrue)]
     pub show_config_path: bool,
+    #[arg(
+        long = "config-file",
+        value_name = "PATH",
+        global = true,
+        help = "load configuration from PATH instead of the default location [env: NT_CONFIG=]"
+    )]
+    pub config_file: Option<PathBuf>,
     #[arg(short = 'i', long = "interactive", action = ArgAction::SetTrue, help = "enter interactive single-line mode (press Enter to submit)")]
     pub interactive: bool,
+    #[arg(
+        short = 'm',
+        long = "multiline",
+        conflicts_with_all = ["interactive", "print", "search", "show_config_path"],
+        help = "read all of stdin (until EOF) as a single multi-line note"
+    )]
+    pub multiline: bool,
+    #[arg(
+        short = 'e',
+        long = "edit-new",
+        conflicts_with_all = ["interactive", "multiline", "print", "search", "show_config_path"],
+        help = "compose a new note in $VISUAL/$EDITOR"
+    )]
+    pub edit_new: bool,
+    #[arg(
+        long = "at",
+        value_name = "WHEN",
+        conflicts_with_all = ["print", "search", "show_config_path"],
+        help = "backdate the note, e.g. \"30m ago\" or \"yesterday 17:00\""
+    )]
+    pub at: Option<String>,
     #[arg(value_name = "NOTE", trailing_var_arg = true)]
     pub note: Vec<String>,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Search note text and print matching notes
+    Search(SearchArgs),
+    /// List tags with their usage count and last-used date
+    Tags(TagsArgs),
+    /// Replace the text of the most recent note, keeping its timestamp
+    Amend(AmendArgs),
+    /// Replace the text of a selected note, keeping its timestamp
+    Edit(EditArgs),
+    /// Move notes to the trash (the most recent note when no selector is given)
+    Rm(RmArgs),
+    /// Restore the notes removed by the last `nt rm`
+    Undo,
+    /// Copy all notes into a new store in another storage format
+    Migrate(MigrateArgs),
+    /// Add notes written by other tools to the note file
+    #[command(subcommand)]
+    Import(ImportSource),
+    /// Write notes as Markdown, HTML, JSON or CSV
+    Export(ExportArgs),
+    /// Inspect the effective configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print every effective setting as nt.toml lines
+    Show(ConfigShowArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigShowArgs {
+    /// Note where each value came from: the default, a config file or an NT_* variable
+    #[arg(long = "origin")]
+    pub origin: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[arg(long = "format", value_name = "FORMAT", default_value = "md")]
+    pub format: ExportFormat,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    /// Write to FILE instead of standard output
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// Convert a daybook written by the old bash `nt` script (`[<timestamp>] text` lines)
+    Legacy(LegacyImportArgs),
+    /// Import jrnl's plain-text export
+    Jrnl(JrnlImportArgs),
+    /// Import notes under dated headings of a markdown file
+    Markdown(MarkdownImportArgs),
+    /// Import rows of a CSV file with a header row
+    Csv(CsvImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct JrnlImportArgs {
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+    /// The journal's timeformat; common jrnl formats are tried when omitted
+    #[arg(long = "datetime-format", value_name = "PATTERN")]
+    pub datetime_format: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct MarkdownImportArgs {
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct CsvImportArgs {
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+    /// Header name or 1-based position of the timestamp column
+    #[arg(
+        long = "timestamp-column",
+        value_name = "COLUMN",
+        default_value = "timestamp"
+    )]
+    pub timestamp_column: String,
+    /// Header name or 1-based position of the note text column
+    #[arg(long = "text-column", value_name = "COLUMN", default_value = "text")]
+    pub text_column: String,
+    /// Pattern of the timestamp column; RFC 3339 and ISO 8601 are tried when omitted
+    #[arg(long = "timestamp-format", value_name = "PATTERN")]
+    pub timestamp_format: Option<String>,
+    #[arg(long = "delimiter", value_name = "CHAR", default_value_t = ',')]
+    pub delimiter: char,
+}
+
+/// A source format for `nt import`, other than the legacy script.
+#[derive(Debug)]
+pub enum ImportFormat {
+    Jrnl {
+        datetime_format: Option<String>,
+    },
+    Markdown,
+    Csv {
+        timestamp_column: String,
+        text_column: String,
+        timestamp_format: Option<String>,
+        delimiter: char,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct LegacyImportArgs {
+    /// Daybook to import; defaults to the script's note file. When it is the configured
+    /// note file, its legacy lines are converted in place
+    #[arg(value_name = "PATH")]
+    pub path: Option<PathBuf>,
+    /// Pattern the script wrote timestamps with; defaults to the script's config
+    #[arg(long = "datetime-format", value_name = "PATTERN")]
+    pub datetime_format: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// Storage format to convert to
+    #[arg(long = "to", value_name = "FORMAT")]
+    pub to: StorageFormat,
+    /// Where to write the new store; defaults to the note file with the format's extension
+    #[arg(long = "output", value_name = "PATH")]
+    pub output: Option<PathBuf>,
+    /// Point the config file at the new store once it is written
+    #[arg(long = "save-config")]
+    pub save_config: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RmArgs {
+    /// Notes to remove, e.g. @1 for the most recent
+    #[arg(value_name = "SELECTOR")]
+    pub selectors: Vec<NoteSelector>,
+    /// Remove every note whose text contains PATTERN (case-insensitive)
+    #[arg(long = "match", value_name = "PATTERN")]
+    pub pattern: Option<String>,
+    /// Do not ask for confirmation
+    #[arg(short = 'y', long = "yes")]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AmendArgs {
+    /// New note text; opens $VISUAL/$EDITOR on the current text when omitted
+    #[arg(value_name = "TEXT", trailing_var_arg = true)]
+    pub text: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct EditArgs {
+    /// Note to edit, e.g. @2 for the second most recent
+    #[arg(value_name = "SELECTOR")]
+    pub selector: NoteSelector,
+    /// New note text; opens $VISUAL/$EDITOR on the current text when omitted
+    #[arg(value_name = "TEXT", trailing_var_arg = true)]
+    pub text: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TagsArgs {
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    #[arg(value_name = "PATTERN")]
+    pub pattern: String,
+    #[command(flatten)]
+    pub mode: SearchModeArgs,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub struct Invocation {
+    pub config_file: Option<PathBuf>,
+    pub action: CommandAction,
+}
+
 pub enum CommandAction {
-    Append { text: String },
-    Print { count: usize },
-    AppendFromStdin,
-    InteractiveAppend,
+    Append {
+        text: String,
+        at: Option<String>,
+    },
+    Print {
+        /// None prints every note that passes the filter.
+        count: Option<usize>,
+        filter: FilterArgs,
+        output: OutputArgs,
+    },
+    Search {
+        pattern: String,
+        mode: SearchModeArgs,
+        filter: FilterArgs,
+        output: OutputArgs,
+    },
+    ListTags {
+        filter: FilterArgs,
+    },
+    EditNote {
+        selector: NoteSelector,
+        text: Option<String>,
+    },
+    RemoveNotes {
+        selectors: Vec<NoteSelector>,
+        pattern: Option<String>,
+        yes: bool,
+    },
+    Undo,
+    Migrate {
+        to: StorageFormat,
+        output: Option<PathBuf>,
+        save_config: bool,
+    },
+    ImportLegacy {
+        path: Option<PathBuf>,
+        datetime_format: Option<String>,
+    },
+    ImportNotes {
+        path: PathBuf,
+        format: ImportFormat,
+    },
+    Export {
+        format: ExportFormat,
+        filter: FilterArgs,
+        output: Option<PathBuf>,
+    },
+    AppendFromStdin {
+        at: Option<String>,
+    },
+    AppendMultilineFromStdin {
+        at: Option<String>,
+    },
+    ComposeInEditor {
+        at: Option<String>,
+    },
+    InteractiveAppend {
+        at: Option<String>,
+    },
     ShowConfigPath,
+    ShowConfig {
+        origin: bool,
+    },
 }
 
 impl Cli {
-    pub fn parse_action() -> Result<CommandAction, clap::Error> {
-        let cli = Cli::parse();
+    pub fn parse_invocation() -> Result<Invocation, clap::Error> {
+        let mut cli = Cli::parse();
+        let config_file = cli.config_file.take().or_else(config_file_from_environment);
+        let action = cli.into_action()?;
+        Ok(Invocation {
+            config_file,
+            action,
+        })
+    }
+
+    pub fn into_action(self) -> Result<CommandAction, clap::Error> {
+        if let Some(command) = self.command {
+            if self.print.is_some()
+                || self.search.is_some()
+                || self.show_config_path
+                || self.interactive
+                || self.multiline
+                || self.edit_new
+                || self.at.is_some()
+                || !self.search_mode.is_empty()
+                || !self.filter.is_empty()
+                || !self.output.is_empty()
+            {
+                return Err(clap::Error::raw(
+                    ErrorKind::ArgumentConflict,
+                    "subcommands cannot be combined with top-level note options",
+                ));
+            }
+            return Ok(match command {
+                Command::Search(args) => CommandAction::Search {
+                    pattern: args.pattern,
+                    mode: args.mode,
+                    filter: args.filter,
+                    output: args.output,
+                },
+                Command::Tags(args) => CommandAction::ListTags {
+                    filter: args.filter,
+                },
+                Command::Amend(args) => CommandAction::EditNote {
+                    selector: NoteSelector::LAST,
+                    text: replacement_text(args.text)?,
+                },
+                Command::Edit(args) => CommandAction::EditNote {
+                    selector: args.selector,
+                    text: replacement_text(args.text)?,
+                },
+                Command::Rm(args) => CommandAction::RemoveNotes {
+                    selectors: if args.selectors.is_empty() && args.pattern.is_none() {
+                        vec![NoteSelector::LAST]
+                    } else {
+                        args.selectors
+                    },
+                    pattern: args.pattern,
+                    yes: args.yes,
+                },
+                Command::Undo => CommandAction::Undo,
+                Command::Migrate(args) => CommandAction::Migrate {
+                    to: args.to,
+                    output: args.output,
+                    save_config: args.save_config,
+                },
+                Command::Import(ImportSource::Legacy(args)) => CommandAction::ImportLegacy {
+                    path: args.path,
+                    datetime_format: args.datetime_format,
+                },
+                Command::Import(ImportSource::Jrnl(args)) => CommandAction::ImportNotes {
+                    path: args.path,
+                    format: ImportFormat::Jrnl {
+                        datetime_format: args.datetime_format,
+                    },
+                },
+                Command::Import(ImportSource::Markdown(args)) => CommandAction::ImportNotes {
+                    path: args.path,
+                    format: ImportFormat::Markdown,
+                },
+                Command::Export(args) => CommandAction::Export {
+                    format: args.format,
+                    filter: args.filter,
+                    output: args.output,
+                },
+                Command::Config(ConfigCommand::Show(args)) => CommandAction::ShowConfig {
+                    origin: args.origin,
+                },
+                Command::Import(ImportSource::Csv(args)) => {
+                    if !args.delimiter.is_ascii() {
+                        return Err(clap::Error::raw(
+                            ErrorKind::InvalidValue,
+                            "--delimiter must be a single ASCII character",
+                        ));
+                    }
+                    CommandAction::ImportNotes {
+                        path: args.path,
+                        format: ImportFormat::Csv {
+                            timestamp_column: args.timestamp_column,
+                            text_column: args.text_column,
+                            timestamp_format: args.timestamp_format,
+                            delimiter: args.delimiter,
+                        },
+                    }
+                }
+            });
+        }
+        if self.print.is_none() && self.search.is_none() && !self.filter.is_empty() {
+            return Err(clap::Error::raw(
+                ErrorKind::ArgumentConflict,
+                "--since/--until/--on/--tag require --print/-p or --search/-s",
+            ));
+        }
+        if self.print.is_none() && self.search.is_none() && !self.output.is_empty() {
+            return Err(clap::Error::raw(
+                ErrorKind::ArgumentConflict,
+                "--json/--format/--tz require --print/-p or --search/-s",
+            ));
+        }
+        if self.search.is_none() && !self.search_mode.is_empty() {
+            return Err(clap::Error::raw(
+                ErrorKind::ArgumentConflict,
+                "--word/--regex/--count require --search/-s",
+            ));
+        }
         // Handle explicit interactive flag first
-        if cli.show_config_path {
-            if cli.print.is_some() || cli.interactive || !cli.note.is_empty() {
+        if self.show_config_path {
+            if self.print.is_some()
+                || self.search.is_some()
+                || self.interactive
+                || !self.note.is_empty()
+            {
                 return Err(clap::Error::raw(
                     ErrorKind::ArgumentConflict,
                     "--config-path cannot be combined with other options or note text",
@@ -34,48 +464,106 @@ impl Cli {
             }
             return Ok(CommandAction::ShowConfigPath);
         }
-        if cli.interactive {
-            if cli.print.is_some() {
+        if self.interactive {
+            if self.print.is_some() || self.search.is_some() {
                 return Err(clap::Error::raw(
                     ErrorKind::ArgumentConflict,
-                    "cannot mix --interactive with --print/-p",
+                    "cannot mix --interactive with --print/-p or --search/-s",
                 ));
             }
-            if !cli.note.is_empty() {
+            if !self.note.is_empty() {
                 return Err(clap::Error::raw(
                     ErrorKind::ArgumentConflict,
                     "cannot supply note text with --interactive",
                 ));
             }
-            return Ok(CommandAction::InteractiveAppend);
+            return Ok(CommandAction::InteractiveAppend { at: self.at });
         }
-        if let Some(opt) = &cli.print {
-            if !cli.note.is_empty() {
+        if self.edit_new {
+            if !self.note.is_empty() {
+                return Err(clap::Error::raw(
+                    ErrorKind::ArgumentConflict,
+                    "cannot supply note text with --edit-new/-e",
+                ));
+            }
+            return Ok(CommandAction::ComposeInEditor { at: self.at });
+        }
+        if self.multiline {
+            if !self.note.is_empty() {
+                return Err(clap::Error::raw(
+                    ErrorKind::ArgumentConflict,
+                    "cannot supply note text with --multiline/-m",
+                ));
+            }
+            return Ok(CommandAction::AppendMultilineFromStdin { at: self.at });
+        }
+        if let Some(pattern) = self.search {
+            if !self.note.is_empty() {
+                return Err(clap::Error::raw(
+                    ErrorKind::ArgumentConflict,
+                    "cannot mix note text with --search/-s",
+                ));
+            }
+            return Ok(CommandAction::Search {
+                pattern,
+                mode: self.search_mode,
+                filter: self.filter,
+                output: self.output,
+            });
+        }
+        if let Some(opt) = &self.print {
+            if !self.note.is_empty() {
                 return Err(clap::Error::raw(
                     ErrorKind::ArgumentConflict,
                     "cannot mix note text with --print/-p",
                 ));
             }
-            let count = opt.unwrap_or(10);
-            return Ok(CommandAction::Print { count });
+            let has_range = self.filter.since.is_some()
+                || self.filter.until.is_some()
+                || self.filter.on.is_some();
+            let count = match opt {
+                Some(count) => Some(*count),
+                None if has_range => None,
+                None => Some(10),
+            };
+            return Ok(CommandAction::Print {
+                count,
+                filter: self.filter,
+                output: self.output,
+            });
         }
-        if cli.note.is_empty() {
+        if self.note.is_empty() {
             // If no note text provided, allow capturing from stdin when stdin is not a TTY.
             use std::io::IsTerminal;
             if std::io::stdin().is_terminal() {
                 // Automatic interactive mode (no args, stdin is TTY)
-                return Ok(CommandAction::InteractiveAppend);
+                return Ok(CommandAction::InteractiveAppend { at: self.at });
             } else {
-                return Ok(CommandAction::AppendFromStdin);
+                return Ok(CommandAction::AppendFromStdin { at: self.at });
             }
         }
-        let text = cli.note.join(" ").trim().to_string();
+        let text = self.note.join(" ").trim().to_string();
         if text.is_empty() {
             return Err(clap::Error::raw(
                 ErrorKind::InvalidValue,
                 "note text cannot be empty",
             ));
         }
-        Ok(CommandAction::Append { text })
+        Ok(CommandAction::Append { text, at: self.at })
+    }
+}
+
+/// Joins replacement note text given as arguments; None means "open the editor".
+fn replacement_text(words: Vec<String>) -> Result<Option<String>, clap::Error> {
+    if words.is_empty() {
+        return Ok(None);
+    }
+    let text = words.join(" ").trim().to_string();
+    if text.is_empty() {
+        return Err(clap::Error::raw(
+            ErrorKind::InvalidValue,
+            "note text cannot be empty",
+        ));
     }
+    Ok(Some(text))
 }
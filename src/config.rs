@@ -1,12 +1,19 @@
 use std::{
+    fmt::{self, Write as _},
     fs,
     io::{self, Read},
     path::{Path, PathBuf},
 };
 
+use chrono::DateTime;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::legacy::{LegacyConfig, legacy_config_file_path};
+use crate::notes::{Durability, NoteEntry, NoteParser, StorageFormat};
+use crate::store::{NoteStore, open_store};
+use crate::time::{Timezone, UnknownTimezone, invalid_strftime_specifier};
+
 pub const DEFAULT_NOTE_FILE_LITERAL: &str = "~/daybook.txt";
 pub const DEFAULT_DATETIME_FORMAT_PATTERN: &str = "%Y-%m-%d %H:%M";
 pub const CONFIG_FILE_NAME: &str = "nt.toml";
@@ -21,6 +28,37 @@ pub enum ConfigLoadSaveError {
     TomlSerialize(#[from] toml::ser::Error),
     #[error("unable to locate user home directory")]
     MissingHomeDirectory,
+    #[error("config file not found: {}", .0.display())]
+    MissingConfigFile(PathBuf),
+    #[error("invalid datetime_format {pattern:?}: {problem}")]
+    InvalidDatetimeFormat { pattern: String, problem: String },
+    #[error("invalid timezone setting: {0}")]
+    InvalidTimezone(#[from] UnknownTimezone),
+    #[error("invalid value in {variable}: {message}")]
+    InvalidEnvironmentValue {
+        variable: &'static str,
+        message: String,
+    },
+}
+
+/// Each config key with the environment variable that overrides it.
+pub const CONFIG_KEYS: &[(&str, &str)] = &[
+    ("note_file", "NT_NOTE_FILE"),
+    ("datetime_format", "NT_DATETIME_FORMAT"),
+    ("durability", "NT_DURABILITY"),
+    ("format", "NT_FORMAT"),
+    ("timezone", "NT_TIMEZONE"),
+];
+
+/// Names the config file when `--config-file` is not given.
+pub const CONFIG_FILE_VARIABLE: &str = "NT_CONFIG";
+
+/// The config file named by `NT_CONFIG`; like the `NT_*` overrides, it is ignored
+/// when empty.
+pub fn config_file_from_environment() -> Option<PathBuf> {
+    std::env::var_os(CONFIG_FILE_VARIABLE)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -29,6 +67,57 @@ struct TomlConfig {
     note_file: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     datetime_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    durability: Option<Durability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<StorageFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+}
+
+impl TomlConfig {
+    /// This layer with the keys set in `over` replaced.
+    fn overlay(self, over: TomlConfig) -> TomlConfig {
+        TomlConfig {
+            note_file: over.note_file.or(self.note_file),
+            datetime_format: over.datetime_format.or(self.datetime_format),
+            durability: over.durability.or(self.durability),
+            format: over.format.or(self.format),
+            timezone: over.timezone.or(self.timezone),
+        }
+    }
+
+    /// The keys this layer sets, with their values.
+    fn to_table(&self) -> Result<toml::Table, ConfigLoadSaveError> {
+        Ok(toml::Table::try_from(self)?)
+    }
+}
+
+/// Where an effective setting came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingOrigin {
+    Default,
+    File(PathBuf),
+    Environment(&'static str),
+}
+
+impl fmt::Display for SettingOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingOrigin::Default => f.write_str("default"),
+            SettingOrigin::File(path) => write!(f, "file {}", path.display()),
+            SettingOrigin::Environment(variable) => write!(f, "environment {variable}"),
+        }
+    }
+}
+
+/// One effective setting, as `nt config show` prints it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    pub key: &'static str,
+    /// The value as written in `nt.toml`, e.g. `"flush"` with the quotes.
+    pub value: String,
+    pub origin: SettingOrigin,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,6 +125,10 @@ pub struct RuntimeConfig {
     pub configured_note_file_literal: String,
     pub expanded_note_file_path: PathBuf,
     pub datetime_format_pattern: String,
+    pub durability: Durability,
+    pub format: StorageFormat,
+    /// Zone new notes are stamped in and offset-less timestamps are read in.
+    pub timezone: Timezone,
 }
 
 pub fn default_config_directory() -> Option<PathBuf> {
@@ -65,6 +158,9 @@ impl Default for RuntimeConfig {
             configured_note_file_literal: DEFAULT_NOTE_FILE_LITERAL.to_string(),
             expanded_note_file_path: expanded_default_note_file,
             datetime_format_pattern: DEFAULT_DATETIME_FORMAT_PATTERN.to_string(),
+            durability: Durability::default(),
+            format: StorageFormat::default(),
+            timezone: Timezone::default(),
         }
     }
 }
@@ -81,26 +177,105 @@ impl RuntimeConfig {
             configured_note_file_literal: note_file_literal,
             expanded_note_file_path,
             datetime_format_pattern,
+            durability: Durability::default(),
+            format: StorageFormat::default(),
+            timezone: Timezone::default(),
         })
     }
 
+    /// Loads `nt.toml` from the default location, with `NT_*` environment variables
+    /// layered over it. When it does not exist, falls back to the bash script's
+    /// `~/.config/nt/config`, then to defaults.
     pub fn load_or_default() -> Result<Self, ConfigLoadSaveError> {
-        let config_file_path = default_config_file_path()?;
-        Self::load_from_path(&config_file_path)
+        Ok(Self::load_with_origins(None)?.0)
+    }
+
+    /// Loads the config file the user named explicitly, with `NT_*` environment
+    /// variables layered over it; unlike the default location, a missing file is an
+    /// error rather than a silent fallback to defaults.
+    pub fn load_from_explicit_path(path: &Path) -> Result<Self, ConfigLoadSaveError> {
+        Ok(Self::load_with_origins(Some(path))?.0)
     }
 
+    /// Loads the config like [`load_from_explicit_path`](Self::load_from_explicit_path)
+    /// when `config_file` is given and [`load_or_default`](Self::load_or_default)
+    /// otherwise, also returning every effective setting and where it came from.
+    pub fn load_with_origins(
+        config_file: Option<&Path>,
+    ) -> Result<(Self, Vec<Setting>), ConfigLoadSaveError> {
+        let (file, file_path) = read_config_file(config_file)?;
+        let environment = read_environment_overrides()?;
+        let file_keys = file.to_table()?;
+        let environment_keys = environment.to_table()?;
+        let config = Self::from_parsed_toml(file.overlay(environment))?;
+
+        let effective = config.to_toml_config().to_table()?;
+        let settings = CONFIG_KEYS
+            .iter()
+            .map(|&(key, variable)| {
+                let origin = if environment_keys.contains_key(key) {
+                    SettingOrigin::Environment(variable)
+                } else if let Some(path) =
+                    file_path.as_ref().filter(|_| file_keys.contains_key(key))
+                {
+                    SettingOrigin::File(path.clone())
+                } else {
+                    SettingOrigin::Default
+                };
+                Setting {
+                    key,
+                    value: effective
+                        .get(key)
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    origin,
+                }
+            })
+            .collect();
+        Ok((config, settings))
+    }
+
+    /// Loads `path` like [`load_from_explicit_path`](Self::load_from_explicit_path),
+    /// except that a missing file gives the defaults, still with `NT_*` variables
+    /// layered over them.
     pub fn load_from_path(path: &Path) -> Result<Self, ConfigLoadSaveError> {
         if !path.exists() {
-            return Ok(RuntimeConfig::default());
+            return Self::from_parsed_toml(read_environment_overrides()?);
         }
-        let mut file_contents = String::new();
-        fs::File::open(path)?.read_to_string(&mut file_contents)?;
-        let parsed: TomlConfig = toml::from_str(&file_contents)?;
-        Self::from_parsed_toml(parsed)
+        Self::load_from_explicit_path(path)
+    }
+
+    /// Points the config at another note store: rewrites `config_file`, or `nt.toml` at
+    /// the default location, with `note_file` and `format` changed and every other key
+    /// kept as the file had it. `NT_*` overrides in effect are never saved.
+    pub fn save_note_store(
+        config_file: Option<&Path>,
+        note_file_literal: String,
+        format: StorageFormat,
+    ) -> Result<(), ConfigLoadSaveError> {
+        let (file, _) = read_config_file(config_file)?;
+        let updated = file.overlay(TomlConfig {
+            note_file: Some(note_file_literal),
+            format: Some(format),
+            ..TomlConfig::default()
+        });
+        let destination_path = match config_file {
+            Some(path) => path.to_path_buf(),
+            None => default_config_file_path()?,
+        };
+        if let Some(parent_directory) = destination_path.parent() {
+            fs::create_dir_all(parent_directory)?;
+        }
+        fs::write(destination_path, toml::to_string_pretty(&updated)?)?;
+        Ok(())
     }
 
     pub fn save(&self) -> Result<(), ConfigLoadSaveError> {
-        let destination_path = default_config_file_path()?;
+        self.save_to_path(&default_config_file_path()?)
+    }
+
+    /// Writes the settings that differ from the defaults to `destination_path`.
+    pub fn save_to_path(&self, destination_path: &Path) -> Result<(), ConfigLoadSaveError> {
         if let Some(parent_directory) = destination_path.parent() {
             fs::create_dir_all(parent_directory)?;
         }
@@ -109,9 +284,38 @@ impl RuntimeConfig {
         Ok(())
     }
 
+    /// The parser for the configured note file's format and datetime pattern.
+    pub fn note_parser(&self) -> NoteParser {
+        NoteParser::with_format(&self.datetime_format_pattern, self.format)
+            .with_timezone(self.timezone)
+    }
+
+    /// Opens the configured note store.
+    pub fn note_store(&self) -> io::Result<Box<dyn NoteStore>> {
+        open_store(
+            self.expanded_note_file_path.clone(),
+            self.note_parser(),
+            self.durability,
+        )
+    }
+
+    /// Every setting, including the ones left at their defaults.
+    fn to_toml_config(&self) -> TomlConfig {
+        TomlConfig {
+            note_file: Some(self.configured_note_file_literal.clone()),
+            datetime_format: Some(self.datetime_format_pattern.clone()),
+            durability: Some(self.durability),
+            format: Some(self.format),
+            timezone: Some(self.timezone.to_string()),
+        }
+    }
+
     fn from_parsed_toml(parsed: TomlConfig) -> Result<Self, ConfigLoadSaveError> {
         let home_directory = dirs::home_dir().ok_or(ConfigLoadSaveError::MissingHomeDirectory)?;
-        RuntimeConfig::from_parts(
+        if let Some(pattern) = &parsed.datetime_format {
+            validate_datetime_format(pattern)?;
+        }
+        let mut config = RuntimeConfig::from_parts(
             parsed
                 .note_file
                 .unwrap_or_else(|| DEFAULT_NOTE_FILE_LITERAL.to_string()),
@@ -119,7 +323,137 @@ impl RuntimeConfig {
                 .datetime_format
                 .unwrap_or_else(|| DEFAULT_DATETIME_FORMAT_PATTERN.to_string()),
             &home_directory,
-        )
+        )?;
+        if let Some(durability) = parsed.durability {
+            config.durability = durability;
+        }
+        if let Some(format) = parsed.format {
+            config.format = format;
+        }
+        if let Some(timezone) = parsed.timezone {
+            config.timezone = timezone.parse()?;
+        }
+        Ok(config)
+    }
+}
+
+/// A config file that exists, by the format it is read in.
+enum ConfigFile {
+    Toml(PathBuf),
+    Legacy(PathBuf),
+}
+
+/// The config file read when none is named: `nt.toml` at the default location, else the
+/// bash script's config; None when neither exists.
+fn find_default_config_file() -> Result<Option<ConfigFile>, ConfigLoadSaveError> {
+    let config_file_path = default_config_file_path()?;
+    if config_file_path.exists() {
+        Ok(Some(ConfigFile::Toml(config_file_path)))
+    } else if let Some(legacy_path) = legacy_config_file_path()
+        && legacy_path.is_file()
+    {
+        Ok(Some(ConfigFile::Legacy(legacy_path)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The config file in effect: `config_file` when given, else the one found at the
+/// default locations, else where `nt.toml` would be created.
+pub fn config_file_in_use(config_file: Option<&Path>) -> Result<PathBuf, ConfigLoadSaveError> {
+    match config_file {
+        Some(path) => Ok(path.to_path_buf()),
+        None => match find_default_config_file()? {
+            Some(ConfigFile::Toml(path) | ConfigFile::Legacy(path)) => Ok(path),
+            None => default_config_file_path(),
+        },
+    }
+}
+
+/// The settings from the config file alone, as [`RuntimeConfig::load_with_origins`]
+/// finds it, with the path they were read from.
+fn read_config_file(
+    config_file: Option<&Path>,
+) -> Result<(TomlConfig, Option<PathBuf>), ConfigLoadSaveError> {
+    match config_file {
+        Some(path) => {
+            if !path.is_file() {
+                return Err(ConfigLoadSaveError::MissingConfigFile(path.to_path_buf()));
+            }
+            Ok((read_toml_config(path)?, Some(path.to_path_buf())))
+        }
+        None => match find_default_config_file()? {
+            Some(ConfigFile::Toml(path)) => Ok((read_toml_config(&path)?, Some(path))),
+            Some(ConfigFile::Legacy(path)) => Ok((read_legacy_config(&path)?, Some(path))),
+            None => Ok((TomlConfig::default(), None)),
+        },
+    }
+}
+
+fn read_toml_config(path: &Path) -> Result<TomlConfig, ConfigLoadSaveError> {
+    let mut file_contents = String::new();
+    fs::File::open(path)?.read_to_string(&mut file_contents)?;
+    Ok(toml::from_str(&file_contents)?)
+}
+
+/// Takes only `note_file` from the bash script's config: its `datetime_format` is a
+/// `date(1)` pattern for `[<timestamp>]` lines, which `nt import legacy` reads with
+/// [`LegacyConfig::datetime_format_pattern`].
+fn read_legacy_config(path: &Path) -> Result<TomlConfig, ConfigLoadSaveError> {
+    let legacy = LegacyConfig::parse(&fs::read_to_string(path)?);
+    Ok(TomlConfig {
+        note_file: legacy.note_file,
+        ..TomlConfig::default()
+    })
+}
+
+/// Reads the `NT_*` variable of each config key that is set and not empty.
+fn read_environment_overrides() -> Result<TomlConfig, ConfigLoadSaveError> {
+    let mut table = toml::Table::new();
+    for &(key, variable) in CONFIG_KEYS {
+        let Some(value) = std::env::var_os(variable).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        let value =
+            value
+                .into_string()
+                .map_err(|_| ConfigLoadSaveError::InvalidEnvironmentValue {
+                    variable,
+                    message: "not valid UTF-8".to_string(),
+                })?;
+        // Check each variable on its own so that errors name it.
+        let single = toml::Table::from_iter([(key.to_string(), toml::Value::String(value))]);
+        single.clone().try_into::<TomlConfig>().map_err(|e| {
+            ConfigLoadSaveError::InvalidEnvironmentValue {
+                variable,
+                message: e.message().to_string(),
+            }
+        })?;
+        table.extend(single);
+    }
+    Ok(table.try_into()?)
+}
+
+/// Checks that notes can be stamped with `pattern` and read back: every specifier is
+/// one chrono knows, and a stamped note parses with a date.
+fn validate_datetime_format(pattern: &str) -> Result<(), ConfigLoadSaveError> {
+    let invalid = |problem: String| ConfigLoadSaveError::InvalidDatetimeFormat {
+        pattern: pattern.to_string(),
+        problem,
+    };
+    if let Some(specifier) = invalid_strftime_specifier(pattern) {
+        return Err(invalid(specifier.to_string()));
+    }
+    let sample = DateTime::parse_from_rfc3339("2001-02-13T14:15:16+00:00")
+        .expect("sample timestamp is valid");
+    let mut line = String::new();
+    write!(line, "{} note", sample.format(pattern))
+        .map_err(|_| invalid("timestamps cannot be written with it".to_string()))?;
+    match NoteParser::new(pattern).parse_line(&line) {
+        NoteEntry::Parsed(note) if note.text == "note" => Ok(()),
+        _ => Err(invalid(
+            "notes stamped with it cannot be read back; it needs at least a full date".to_string(),
+        )),
     }
 }
 
@@ -131,5 +465,14 @@ pub fn serialize_diff_from_default(cfg: &RuntimeConfig) -> Result<String, Config
     if cfg.datetime_format_pattern != DEFAULT_DATETIME_FORMAT_PATTERN {
         toml_config.datetime_format = Some(cfg.datetime_format_pattern.clone());
     }
+    if cfg.durability != Durability::default() {
+        toml_config.durability = Some(cfg.durability);
+    }
+    if cfg.format != StorageFormat::default() {
+        toml_config.format = Some(cfg.format);
+    }
+    if cfg.timezone != Timezone::default() {
+        toml_config.timezone = Some(cfg.timezone.to_string());
+    }
     Ok(toml::to_string_pretty(&toml_config)?)
 }
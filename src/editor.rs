@@ -0,0 +1,96 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::notes::normalize_note_body;
+
+/// Lines starting with this prefix are template comments and are dropped from the buffer.
+/// (`#:` can never start a tag, so notes may still begin with `#tag`.)
+pub const EDITOR_COMMENT_PREFIX: &str = "#:";
+
+const TEMPLATE_HEADER: &str = "#: Write the note above. Lines starting with \"#:\" are ignored.\n\
+#: Leave the note empty or unchanged to abort.\n";
+
+pub enum EditOutcome {
+    Edited(String),
+    Unchanged,
+    Empty,
+}
+
+/// The editor command from `$VISUAL`, then `$EDITOR`, falling back to `vi`.
+pub fn resolve_editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Opens `editor` on a buffer pre-filled with `initial_text` and the comment template,
+/// and returns the note text the user left behind.
+pub fn edit_note_text(editor: &str, initial_text: &str) -> io::Result<EditOutcome> {
+    let mut template = String::new();
+    if !initial_text.is_empty() {
+        template.push_str(initial_text);
+        template.push('\n');
+    }
+    template.push('\n');
+    template.push_str(TEMPLATE_HEADER);
+
+    let buffer = edit_buffer(editor, &template)?;
+    let Some(text) = normalize_note_body(&strip_comment_lines(&buffer)) else {
+        return Ok(EditOutcome::Empty);
+    };
+    if normalize_note_body(initial_text).as_deref() == Some(text.as_str()) {
+        return Ok(EditOutcome::Unchanged);
+    }
+    Ok(EditOutcome::Edited(text))
+}
+
+pub fn strip_comment_lines(buffer: &str) -> String {
+    buffer
+        .lines()
+        .filter(|line| !line.starts_with(EDITOR_COMMENT_PREFIX))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes `contents` to a fresh temp file, runs `editor` on it through the shell (so
+/// commands such as `code --wait` work), and returns the file contents afterwards.
+fn edit_buffer(editor: &str, contents: &str) -> io::Result<String> {
+    let path = create_temp_buffer(contents)?;
+    let result = run_editor(editor, &path).and_then(|()| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn run_editor(editor: &str, path: &Path) -> io::Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "editor `{editor}` exited with {status}"
+        )));
+    }
+    Ok(())
+}
+
+fn create_temp_buffer(contents: &str) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("nt-note-{}-{nanos}.txt", std::process::id()));
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(path)
+}
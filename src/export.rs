@@ -0,0 +1,212 @@
+//! Writing notes out for other tools: Markdown and HTML pages grouped by day, and JSON
+//! or CSV for further processing.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use chrono::{NaiveDate, SecondsFormat};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::notes::{Note, NoteEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Markdown with a heading per day, readable by `nt import markdown`
+    Md,
+    /// A self-contained HTML page with a section per day
+    Html,
+    /// A JSON array of `{"ts", "text", "tags", "id"}` objects
+    Json,
+    /// CSV with `timestamp,text,tags,id` columns, readable by `nt import csv`
+    Csv,
+}
+
+/// Writes `entries` to `writer` in `format`. Entries without a parseable timestamp are
+/// kept: under an "Undated" heading for Markdown and HTML, and with an empty timestamp
+/// for JSON and CSV.
+pub fn write_export<W: Write>(
+    writer: &mut W,
+    format: ExportFormat,
+    entries: &[NoteEntry],
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Md => write_markdown(writer, entries),
+        ExportFormat::Html => write_html(writer, entries),
+        ExportFormat::Json => write_json(writer, entries),
+        ExportFormat::Csv => write_csv(writer, entries),
+    }
+}
+
+/// Parsed notes by day in timestamp order, and the raw unparsed entries.
+struct Days<'a> {
+    days: BTreeMap<NaiveDate, Vec<&'a Note>>,
+    undated: Vec<&'a str>,
+}
+
+impl<'a> Days<'a> {
+    fn new(entries: &'a [NoteEntry]) -> Self {
+        let mut days: BTreeMap<NaiveDate, Vec<&Note>> = BTreeMap::new();
+        let mut undated = Vec::new();
+        for entry in entries {
+            match entry {
+                NoteEntry::Parsed(note) => days
+                    .entry(note.timestamp.date_naive())
+                    .or_default()
+                    .push(note),
+                NoteEntry::Unparsed(raw) => undated.push(raw.as_str()),
+            }
+        }
+        for notes in days.values_mut() {
+            notes.sort_by_key(|n| n.timestamp);
+        }
+        Self { days, undated }
+    }
+}
+
+/// Markdown headings are plain dates, which `nt import markdown` reads back.
+const MARKDOWN_DAY_HEADING_FORMAT: &str = "%Y-%m-%d";
+const DAY_HEADING_FORMAT: &str = "%A, %Y-%m-%d";
+const TIME_OF_DAY_FORMAT: &str = "%H:%M";
+
+fn write_markdown<W: Write>(writer: &mut W, entries: &[NoteEntry]) -> io::Result<()> {
+    let days = Days::new(entries);
+    let mut first = true;
+    let mut heading = |writer: &mut W, title: &str| {
+        let separator = if first { "" } else { "\n" };
+        first = false;
+        writeln!(writer, "{separator}## {title}\n")
+    };
+    for (day, notes) in &days.days {
+        heading(writer, &day.format(MARKDOWN_DAY_HEADING_FORMAT).to_string())?;
+        for note in notes {
+            let time = note.timestamp.format(TIME_OF_DAY_FORMAT);
+            writeln!(writer, "- {time} {}", note.text.replace('\n', "\n  "))?;
+        }
+    }
+    if !days.undated.is_empty() {
+        heading(writer, "Undated")?;
+        for raw in &days.undated {
+            writeln!(writer, "- {}", raw.replace('\n', "\n  "))?;
+        }
+    }
+    Ok(())
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #222; line-height: 1.5; }
+h2 { font-size: 1.1rem; border-bottom: 1px solid #ddd; padding-bottom: 0.25rem; margin-top: 2rem; }
+ul { list-style: none; padding: 0; }
+li { display: flex; gap: 1rem; margin: 0.4rem 0; }
+time { color: #777; font-variant-numeric: tabular-nums; flex: none; }
+.text { white-space: pre-wrap; }
+";
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_html<W: Write>(writer: &mut W, entries: &[NoteEntry]) -> io::Result<()> {
+    let days = Days::new(entries);
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html lang=\"en\">")?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, "<meta charset=\"utf-8\">")?;
+    writeln!(writer, "<title>Notes</title>")?;
+    writeln!(writer, "<style>\n{HTML_STYLE}</style>")?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    for (day, notes) in &days.days {
+        writeln!(writer, "<section>")?;
+        writeln!(writer, "<h2>{}</h2>", day.format(DAY_HEADING_FORMAT))?;
+        writeln!(writer, "<ul>")?;
+        for note in notes {
+            writeln!(
+                writer,
+                "<li><time datetime=\"{}\">{}</time><span class=\"text\">{}</span></li>",
+                note.timestamp.to_rfc3339_opts(SecondsFormat::Secs, false),
+                note.timestamp.format(TIME_OF_DAY_FORMAT),
+                escape_html(&note.text)
+            )?;
+        }
+        writeln!(writer, "</ul>")?;
+        writeln!(writer, "</section>")?;
+    }
+    if !days.undated.is_empty() {
+        writeln!(writer, "<section>")?;
+        writeln!(writer, "<h2>Undated</h2>")?;
+        writeln!(writer, "<ul>")?;
+        for raw in &days.undated {
+            writeln!(
+                writer,
+                "<li><span class=\"text\">{}</span></li>",
+                escape_html(raw)
+            )?;
+        }
+        writeln!(writer, "</ul>")?;
+        writeln!(writer, "</section>")?;
+    }
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")
+}
+
+/// An entry as written by the JSON and CSV exports.
+#[derive(Debug, Serialize)]
+pub struct ExportRecord<'a> {
+    /// RFC 3339; None for entries without a parseable timestamp.
+    pub ts: Option<String>,
+    pub text: &'a str,
+    pub tags: &'a [String],
+    pub id: String,
+}
+
+impl<'a> ExportRecord<'a> {
+    pub fn new(entry: &'a NoteEntry) -> Self {
+        match entry {
+            NoteEntry::Parsed(note) => Self {
+                ts: Some(note.timestamp.to_rfc3339_opts(SecondsFormat::Secs, false)),
+                text: &note.text,
+                tags: &note.tags,
+                id: note.id.clone(),
+            },
+            NoteEntry::Unparsed(raw) => Self {
+                ts: None,
+                text: raw,
+                tags: &[],
+                id: entry.id(),
+            },
+        }
+    }
+}
+
+fn write_json<W: Write>(writer: &mut W, entries: &[NoteEntry]) -> io::Result<()> {
+    let records: Vec<ExportRecord> = entries.iter().map(ExportRecord::new).collect();
+    serde_json::to_writer_pretty(&mut *writer, &records)?;
+    writeln!(writer)
+}
+
+fn write_csv<W: Write>(writer: &mut W, entries: &[NoteEntry]) -> io::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(["timestamp", "text", "tags", "id"])?;
+    for entry in entries {
+        let record = ExportRecord::new(entry);
+        csv.write_record([
+            record.ts.as_deref().unwrap_or_default(),
+            record.text,
+            &record.tags.join(" "),
+            &record.id,
+        ])?;
+    }
+    csv.flush()
+}
@@ -0,0 +1,342 @@
+//! Reading notes exported by other tools and merging them into a [`NoteStore`].
+//!
+//! Each source format is a [`NoteImporter`] that turns a file's contents into notes;
+//! [`merge_notes`] then adds them to the store in timestamp order.
+
+use std::collections::HashSet;
+use std::io;
+
+use chrono::{DateTime, FixedOffset, NaiveTime};
+
+use crate::notes::{Note, normalize_note_body};
+use crate::store::NoteStore;
+use crate::time::{TimePoint, Timezone, parse_absolute_time_point, parse_time_point_prefix};
+
+/// Notes read from an exported file, plus the lines that could not be placed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedNotes {
+    pub notes: Vec<Note>,
+    /// 1-based line numbers and contents of input that was not imported.
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// A source format that [`merge_notes`] can import from.
+pub trait NoteImporter {
+    /// Reads notes from the contents of an exported file.
+    fn parse(&self, input: &str) -> io::Result<ParsedNotes>;
+}
+
+/// What [`merge_notes`] added to the store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub added: usize,
+    /// Notes skipped because the store (or the import itself) already has a note with
+    /// the same timestamp and text.
+    pub duplicates: usize,
+}
+
+/// Adds `notes` to `store` in a single rewrite, each placed after the existing notes
+/// that are not newer than it. Existing records keep their order, and exact duplicates
+/// are skipped, so importing the same file twice adds nothing the second time.
+pub fn merge_notes<S: NoteStore + ?Sized>(store: &S, notes: Vec<Note>) -> io::Result<MergeReport> {
+    let lock = store.lock()?;
+    let parser = store.parser();
+    let records = store.read_records()?.unwrap_or_default();
+    let timestamps: Vec<Option<DateTime<FixedOffset>>> = records
+        .iter()
+        .map(|r| parser.parse_line(r).as_note().map(|n| n.timestamp))
+        .collect();
+    let mut seen: HashSet<(DateTime<FixedOffset>, String)> = records
+        .iter()
+        .filter_map(|r| {
+            parser
+                .parse_line(r)
+                .as_note()
+                .map(|n| (n.timestamp, n.text.clone()))
+        })
+        .collect();
+
+    let mut report = MergeReport::default();
+    let mut incoming: Vec<Note> = notes
+        .into_iter()
+        .filter(|n| {
+            // Compare notes as the store keeps them, e.g. without the seconds.
+            let stored = parser.parse_line(&parser.format_note(n));
+            let key = match stored.as_note() {
+                Some(stored) => (stored.timestamp, stored.text.clone()),
+                None => (n.timestamp, n.text.clone()),
+            };
+            let new = seen.insert(key);
+            if !new {
+                report.duplicates += 1;
+            }
+            new
+        })
+        .collect();
+    if incoming.is_empty() {
+        lock.release()?;
+        return Ok(report);
+    }
+    incoming.sort_by_key(|n| n.timestamp);
+    report.added = incoming.len();
+
+    let mut incoming = incoming.into_iter().peekable();
+    let mut merged = Vec::with_capacity(records.len() + report.added);
+    for (record, timestamp) in records.into_iter().zip(timestamps) {
+        if let Some(timestamp) = timestamp {
+            while let Some(note) = incoming.next_if(|n| n.timestamp < timestamp) {
+                merged.push(parser.format_note(&note));
+            }
+        }
+        merged.push(record);
+    }
+    merged.extend(incoming.map(|n| parser.format_note(&n)));
+    store.rewrite(&merged)?;
+    lock.release()?;
+    Ok(report)
+}
+
+/// Timestamp patterns tried for jrnl entries when none is given: jrnl's own journal
+/// format first, then the forms its exports use.
+const JRNL_DATETIME_PATTERNS: &[&str] = &[
+    "%Y-%m-%d %I:%M:%S %p",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %I:%M %p",
+    "%Y-%m-%d %H:%M",
+];
+
+/// jrnl's plain-text export: entries start with a `[<timestamp>] <title>` line (the
+/// brackets are optional) and run until the next entry; the title and body become the
+/// note text.
+#[derive(Debug, Clone, Default)]
+pub struct JrnlImporter {
+    /// The journal's `timeformat`; the common jrnl patterns are tried when None.
+    pub pattern: Option<String>,
+    /// The zone timestamps without an offset are read in.
+    pub timezone: Timezone,
+}
+
+impl JrnlImporter {
+    fn parse_header<'a>(&self, line: &'a str) -> Option<(DateTime<FixedOffset>, &'a str)> {
+        let (bracketed, rest) = match line.strip_prefix('[') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let patterns = match &self.pattern {
+            Some(pattern) => vec![pattern.as_str()],
+            None => JRNL_DATETIME_PATTERNS.to_vec(),
+        };
+        patterns.into_iter().find_map(|pattern| {
+            let (point, remainder) = parse_time_point_prefix(rest, pattern, self.timezone)?;
+            let title = if bracketed {
+                remainder.strip_prefix(']')?
+            } else if remainder.is_empty() || remainder.starts_with(' ') {
+                remainder
+            } else {
+                return None;
+            };
+            Some((
+                point.start(self.timezone)?,
+                title.strip_prefix(' ').unwrap_or(title),
+            ))
+        })
+    }
+}
+
+/// A jrnl entry being read: its header line number, timestamp and lines so far.
+type JrnlEntry<'a> = (usize, DateTime<FixedOffset>, Vec<&'a str>);
+
+fn finish_jrnl_entry(entry: Option<JrnlEntry<'_>>, parsed: &mut ParsedNotes) {
+    let Some((line, timestamp, lines)) = entry else {
+        return;
+    };
+    match normalize_note_body(&lines.join("\n")) {
+        Some(text) => parsed.notes.push(Note::new(timestamp, text)),
+        None => parsed.skipped.push((line, lines.join("\n"))),
+    }
+}
+
+impl NoteImporter for JrnlImporter {
+    fn parse(&self, input: &str) -> io::Result<ParsedNotes> {
+        let mut parsed = ParsedNotes::default();
+        let mut current: Option<JrnlEntry<'_>> = None;
+        for (index, line) in input.lines().enumerate() {
+            if let Some((timestamp, title)) = self.parse_header(line) {
+                finish_jrnl_entry(current.take(), &mut parsed);
+                current = Some((index + 1, timestamp, vec![title]));
+            } else if let Some((_, _, lines)) = &mut current {
+                lines.push(line);
+            } else if !line.trim().is_empty() {
+                parsed.skipped.push((index + 1, line.to_string()));
+            }
+        }
+        finish_jrnl_entry(current, &mut parsed);
+        Ok(parsed)
+    }
+}
+
+/// Markdown with dated headings, e.g. `## 2025-03-07`. Each list item or paragraph
+/// under such a heading becomes a note; an item starting with `HH:MM` is timestamped at
+/// that time of day, others at the heading's. Content under other headings is skipped.
+#[derive(Debug, Clone)]
+pub struct MarkdownImporter {
+    /// Tried for headings before the ISO 8601 forms.
+    pub pattern: String,
+    /// The zone headings and times of day are read in.
+    pub timezone: Timezone,
+}
+
+impl MarkdownImporter {
+    fn note(&self, heading: TimePoint, lines: &[&str]) -> Option<Note> {
+        let text = normalize_note_body(&lines.join("\n"))?;
+        if let TimePoint::Day(date) = heading
+            && let Some((first, rest)) = text.split_once(' ')
+            && let Ok(time) = NaiveTime::parse_from_str(first, "%H:%M")
+            && let Some(timestamp) = self.timezone.resolve(date.and_time(time))
+        {
+            let rest = rest.trim_start();
+            let rest = rest.strip_prefix("- ").unwrap_or(rest);
+            return Some(Note::new(timestamp, rest.to_string()));
+        }
+        Some(Note::new(heading.start(self.timezone)?, text))
+    }
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+        .or_else(|| {
+            let (number, rest) = trimmed.split_once(". ")?;
+            (!number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())).then_some(rest)
+        })
+}
+
+impl NoteImporter for MarkdownImporter {
+    fn parse(&self, input: &str) -> io::Result<ParsedNotes> {
+        let mut parsed = ParsedNotes::default();
+        let mut heading: Option<TimePoint> = None;
+        // The item or paragraph being read, with the line it starts on.
+        let mut item: Vec<&str> = Vec::new();
+        let mut item_line = 0;
+        let finish = |heading: Option<TimePoint>,
+                      item: &mut Vec<&str>,
+                      item_line: usize,
+                      parsed: &mut ParsedNotes| {
+            if let Some(heading) = heading
+                && !item.is_empty()
+            {
+                match self.note(heading, item) {
+                    Some(note) => parsed.notes.push(note),
+                    None => parsed.skipped.push((item_line, item.join("\n"))),
+                }
+            }
+            item.clear();
+        };
+        for (index, line) in input.lines().enumerate() {
+            let trimmed = line.trim();
+            if let Some(title) = trimmed
+                .strip_prefix('#')
+                .map(|t| t.trim_start_matches('#'))
+                .filter(|t| t.starts_with(' '))
+            {
+                finish(heading, &mut item, item_line, &mut parsed);
+                heading = parse_absolute_time_point(title, &self.pattern, self.timezone);
+            } else if trimmed.is_empty() {
+                finish(heading, &mut item, item_line, &mut parsed);
+            } else if heading.is_none() {
+                parsed.skipped.push((index + 1, line.to_string()));
+            } else if let Some(rest) = list_item(line) {
+                finish(heading, &mut item, item_line, &mut parsed);
+                item_line = index + 1;
+                item.push(rest);
+            } else {
+                if item.is_empty() {
+                    item_line = index + 1;
+                }
+                item.push(trimmed);
+            }
+        }
+        finish(heading, &mut item, item_line, &mut parsed);
+        Ok(parsed)
+    }
+}
+
+/// CSV with a header row. Columns are chosen by header name or 1-based position.
+#[derive(Debug, Clone)]
+pub struct CsvImporter {
+    pub timestamp_column: String,
+    pub text_column: String,
+    /// Pattern for the timestamp column; when None, RFC 3339, `pattern` and the ISO 8601
+    /// forms are tried.
+    pub timestamp_format: Option<String>,
+    pub pattern: String,
+    pub delimiter: u8,
+    /// The zone timestamps without an offset are read in.
+    pub timezone: Timezone,
+}
+
+impl CsvImporter {
+    fn column(headers: &csv::StringRecord, column: &str) -> io::Result<usize> {
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(column))
+            .or_else(|| {
+                column
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .filter(|&n| n < headers.len())
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no CSV column named {column:?}"),
+                )
+            })
+    }
+
+    fn timestamp(&self, value: &str) -> Option<DateTime<FixedOffset>> {
+        let point = match &self.timestamp_format {
+            Some(format) => match parse_time_point_prefix(value.trim(), format, self.timezone)? {
+                (point, "") => point,
+                _ => return None,
+            },
+            None => parse_absolute_time_point(value, &self.pattern, self.timezone)?,
+        };
+        point.start(self.timezone)
+    }
+}
+
+impl NoteImporter for CsvImporter {
+    fn parse(&self, input: &str) -> io::Result<ParsedNotes> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .flexible(true)
+            .from_reader(input.as_bytes());
+        let headers = reader.headers().map_err(io::Error::other)?.clone();
+        let timestamp_column = Self::column(&headers, &self.timestamp_column)?;
+        let text_column = Self::column(&headers, &self.text_column)?;
+        let mut parsed = ParsedNotes::default();
+        for row in reader.records() {
+            let row = row.map_err(io::Error::other)?;
+            let line = row.position().map_or(0, |p| p.line() as usize);
+            let note = row
+                .get(timestamp_column)
+                .and_then(|ts| self.timestamp(ts))
+                .zip(row.get(text_column).and_then(normalize_note_body))
+                .map(|(timestamp, text)| Note::new(timestamp, text));
+            match note {
+                Some(note) => parsed.notes.push(note),
+                None => parsed.skipped.push((
+                    line,
+                    row.iter()
+                        .collect::<Vec<_>>()
+                        .join(&char::from(self.delimiter).to_string()),
+                )),
+            }
+        }
+        Ok(parsed)
+    }
+}
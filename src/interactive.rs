@@ -1,7 +1,6 @@
 use std::io::{self, BufRead, Write};
-use std::path::Path;
 
-use crate::notes::append_note_line_to_file_with_clock;
+use crate::store::NoteStore;
 use crate::time::Clock;
 
 pub enum InteractiveOutcome {
@@ -9,13 +8,12 @@ pub enum InteractiveOutcome {
     Empty,
 }
 
-pub fn run_interactive_session<C: Clock, R: BufRead, W: Write>(
+pub fn run_interactive_session<S: NoteStore + ?Sized, R: BufRead, W: Write>(
     reader: &mut R,
     mut writer: W,
     prompt_enabled: bool,
-    clock: &C,
-    datetime_pattern: &str,
-    note_file_path: &Path,
+    clock: &dyn Clock,
+    store: &S,
 ) -> io::Result<InteractiveOutcome> {
     let mut line_buf = String::new();
     if prompt_enabled {
@@ -36,6 +34,6 @@ pub fn run_interactive_session<C: Clock, R: BufRead, W: Write>(
     if line_buf.trim().is_empty() {
         return Ok(InteractiveOutcome::Empty);
     }
-    append_note_line_to_file_with_clock(note_file_path, clock, datetime_pattern, &line_buf)?;
+    store.append_note(clock, &line_buf)?;
     Ok(InteractiveOutcome::Added(1))
 }
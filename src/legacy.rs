@@ -0,0 +1,169 @@
+//! Compatibility with the original bash `nt` script.
+//!
+//! The script appends `[<timestamp>] <text>` lines, with timestamps written by
+//! `date +"<datetime_format>"`, and reads its settings from `~/.config/nt/config` as
+//! `key=value` lines.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::DEFAULT_NOTE_FILE_LITERAL;
+use crate::import::{NoteImporter, ParsedNotes};
+use crate::notes::{Note, NoteEntry, NoteParser};
+use crate::store::NoteStore;
+use crate::time::{Timezone, parse_time_point_prefix};
+
+/// The script's default `datetime_format`, e.g. `14:05 - 3/7/25`.
+pub const LEGACY_DATETIME_FORMAT_PATTERN: &str = "%H:%M - %-m/%-d/%y";
+
+/// `~/.config/nt/config`, where the script looked for its settings on every platform.
+pub fn legacy_config_file_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("nt").join("config"))
+}
+
+/// Settings read from the script's config file; keys it did not set are None.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyConfig {
+    pub note_file: Option<String>,
+    pub datetime_format: Option<String>,
+}
+
+impl LegacyConfig {
+    /// Parses `key=value` lines the way the script did: surrounding whitespace is
+    /// trimmed, blank lines, `#` comments and unknown keys are ignored, and one pair
+    /// of matching quotes around a value is removed.
+    pub fn parse(contents: &str) -> Self {
+        let mut config = Self::default();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+                .unwrap_or(value)
+                .to_string();
+            match key.trim() {
+                "note_file" => config.note_file = Some(value),
+                "datetime_format" => config.datetime_format = Some(value),
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Reads the config file at `path`; None when it does not exist.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(Self::parse(&contents))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The note file the script wrote to, before `~` expansion.
+    pub fn note_file_literal(&self) -> &str {
+        self.note_file
+            .as_deref()
+            .unwrap_or(DEFAULT_NOTE_FILE_LITERAL)
+    }
+
+    /// The pattern the script stamped notes with.
+    pub fn datetime_format_pattern(&self) -> &str {
+        self.datetime_format
+            .as_deref()
+            .unwrap_or(LEGACY_DATETIME_FORMAT_PATTERN)
+    }
+}
+
+/// Parses one `[<timestamp>] <text>` line written by the script with `pattern`, in the
+/// zone the script ran in.
+pub fn parse_legacy_line(line: &str, pattern: &str, timezone: Timezone) -> Option<Note> {
+    let (point, remainder) = parse_time_point_prefix(line.strip_prefix('[')?, pattern, timezone)?;
+    let text = remainder.strip_prefix(']')?;
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    Some(Note::new(point.start(timezone)?, text.to_string()))
+}
+
+/// What a legacy import converted and what it left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyImportReport {
+    /// Legacy lines converted to the store's format.
+    pub imported: usize,
+    /// Records that are neither legacy lines nor notes in the store's format, by 1-based
+    /// position, with their contents. They are kept unchanged.
+    pub unparsed: Vec<(usize, String)>,
+}
+
+/// Converts `records` that are legacy lines to records for `parser`. Records that are
+/// already valid for `parser` are kept, as is anything else, which is also reported.
+fn convert_legacy_records(
+    records: Vec<String>,
+    pattern: &str,
+    parser: &NoteParser,
+    report: &mut LegacyImportReport,
+) -> Vec<String> {
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| {
+            if let Some(note) = parse_legacy_line(&record, pattern, parser.timezone()) {
+                report.imported += 1;
+                parser.format_note(&note)
+            } else {
+                if let NoteEntry::Unparsed(_) = parser.parse_line(&record) {
+                    report.unparsed.push((index + 1, record.clone()));
+                }
+                record
+            }
+        })
+        .collect()
+}
+
+/// Reads a daybook written by the script as a [`NoteImporter`], for merging into a
+/// store that is not the daybook itself.
+#[derive(Debug, Clone)]
+pub struct LegacyImporter {
+    /// The pattern the script wrote timestamps with.
+    pub pattern: String,
+    /// The zone the script ran in, which its timestamps are read in.
+    pub timezone: Timezone,
+}
+
+impl NoteImporter for LegacyImporter {
+    fn parse(&self, input: &str) -> io::Result<ParsedNotes> {
+        let mut parsed = ParsedNotes::default();
+        for (index, line) in input.lines().enumerate() {
+            match parse_legacy_line(line, &self.pattern, self.timezone) {
+                Some(note) => parsed.notes.push(note),
+                None if line.trim().is_empty() => {}
+                None => parsed.skipped.push((index + 1, line.to_string())),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Converts the legacy lines already in `store`, e.g. a daybook the script wrote that
+/// is now the configured note file, leaving everything else in place.
+pub fn convert_legacy_in_place<S: NoteStore + ?Sized>(
+    store: &S,
+    pattern: &str,
+) -> io::Result<Option<LegacyImportReport>> {
+    let lock = store.lock()?;
+    let Some(records) = store.read_records()? else {
+        return Ok(None);
+    };
+    let mut report = LegacyImportReport::default();
+    let converted = convert_legacy_records(records, pattern, store.parser(), &mut report);
+    if report.imported > 0 {
+        store.rewrite(&converted)?;
+    }
+    lock.release()?;
+    Ok(Some(report))
+}
@@ -1,5 +1,19 @@
 pub mod cli;
 pub mod config;
+pub mod editor;
+pub mod export;
+pub mod import;
 pub mod interactive;
+pub mod legacy;
+pub mod lock;
+pub mod migrate;
 pub mod notes;
+pub mod output;
+pub mod query;
+pub mod search;
+pub mod selector;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod store;
 pub mod time;
+pub mod trash;
@@ -0,0 +1,64 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a writer waits for another `nt` process to release the note file.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The lock file that sits next to a note file: `daybook.txt` → `daybook.txt.lock`.
+///
+/// Rewrites replace the note file by renaming over it, so the lock lives in a
+/// separate file whose inode never changes.
+pub fn lock_file_path(note_file: &Path) -> PathBuf {
+    let mut name = note_file
+        .file_name()
+        .map(OsString::from)
+        .unwrap_or_default();
+    name.push(".lock");
+    note_file.with_file_name(name)
+}
+
+/// An exclusive advisory lock (`flock`) on a note file, released on drop.
+///
+/// Every append and every read-modify-write of the note file or its trash
+/// happens while holding this lock. The trash also has a lock of its own, taken
+/// after the note file's, so it can be updated once a store's writes are committed.
+#[derive(Debug)]
+pub struct NoteLock {
+    _file: File,
+}
+
+impl NoteLock {
+    /// Blocks until the lock is free, giving up with `ErrorKind::TimedOut` after `timeout`.
+    pub fn acquire(note_file: &Path, timeout: Duration) -> io::Result<Self> {
+        let path = lock_file_path(note_file);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(NoteLock { _file: file }),
+                Err(TryLockError::Error(e)) => return Err(e),
+                Err(TryLockError::WouldBlock) if Instant::now() >= deadline => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "timed out after {}s waiting for {}; is another nt process stuck?",
+                            timeout.as_secs_f32(),
+                            path.display()
+                        ),
+                    ));
+                }
+                Err(TryLockError::WouldBlock) => thread::sleep(RETRY_INTERVAL),
+            }
+        }
+    }
+}
@@ -1,27 +1,175 @@
-use nt::cli::{Cli, CommandAction};
-use nt::config::RuntimeConfig;
+use nt::cli::{Cli, CommandAction, ImportFormat};
+use nt::config::{RuntimeConfig, expand_leading_tilde_literal};
+use nt::editor::{EditOutcome, edit_note_text, resolve_editor_command};
+use nt::export::write_export;
+use nt::import::{CsvImporter, JrnlImporter, MarkdownImporter, NoteImporter, merge_notes};
 use nt::interactive::{InteractiveOutcome, run_interactive_session};
-use nt::notes::append_note_line_to_file_with_clock;
-use nt::time::SystemClock;
+use nt::legacy::{LegacyConfig, LegacyImporter, convert_legacy_in_place, legacy_config_file_path};
+use nt::migrate::migrate_notes;
+use nt::notes::{NoteParser, normalize_note_body, summarize_tags};
+use nt::output::{NoteOutput, OutputArgs};
+use nt::query::{FilterArgs, NoteFilter, QueryError};
+use nt::search::{NoteMatcher, SearchMode};
+use nt::store::{NoteStore, StoreLock, open_store};
+use nt::time::{Clock, FixedClock, parse_time_point};
+use nt::trash::{move_records_to_trash, restore_last_trashed, trash_file_path};
 
-fn main() {
-    let action = match Cli::parse_action() {
-        Ok(v) => v,
+/// Exit status when the note store does not exist yet, so scripts can tell "no notes"
+/// apart from errors (1) and usage mistakes (2).
+const EXIT_NO_NOTES: i32 = 3;
+
+fn exit_no_notes() -> ! {
+    eprintln!("no notes have been made");
+    std::process::exit(EXIT_NO_NOTES);
+}
+
+fn read_records_or_exit(store: &dyn NoteStore) -> Vec<String> {
+    match store.read_records() {
+        Ok(Some(r)) => r,
+        Ok(None) => exit_no_notes(),
         Err(e) => {
-            let _ = e.print();
+            eprintln!("read error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Takes the store lock before a rewrite and re-reads the records. Notes appended since
+/// `snapshot` was read (e.g. while an editor or confirmation prompt was open) are kept;
+/// any other change aborts, since indices resolved against `snapshot` may be stale.
+fn relock_records_or_exit<'a>(
+    store: &'a dyn NoteStore,
+    snapshot: &[String],
+) -> (StoreLock<'a>, Vec<String>) {
+    let lock = match store.lock() {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("lock error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let records = read_records_or_exit(store);
+    // `snapshot` was read without the lock, so its last record may be a note that was
+    // still being appended; it only has to be the start of that note now.
+    let unchanged = match snapshot.split_last() {
+        Some((last, complete)) => {
+            records.starts_with(complete)
+                && records
+                    .get(complete.len())
+                    .is_some_and(|record| record.starts_with(last.as_str()))
+        }
+        None => true,
+    };
+    if !unchanged {
+        eprintln!("note file was changed by another process; nothing was written");
+        std::process::exit(1);
+    }
+    (lock, records)
+}
+
+/// Releases a lock taken by [`relock_records_or_exit`], exiting if the writes made
+/// under it could not be kept.
+fn release_or_exit(lock: StoreLock<'_>) {
+    if let Err(e) = lock.release() {
+        eprintln!("write error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Asks a yes/no question on stderr and reads the answer from stdin; defaults to no.
+fn confirm(question: &str) -> bool {
+    use std::io::{BufRead, Write};
+    eprint!("{question} [y/N] ");
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn note_output_or_exit(args: &OutputArgs) -> Option<NoteOutput> {
+    match args.note_output() {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{e}");
             std::process::exit(2);
         }
+    }
+}
+
+fn note_filter_or_exit(args: &FilterArgs, parser: &NoteParser) -> NoteFilter {
+    match NoteFilter::from_filter_args(args, parser.pattern(), &parser.timezone()) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// The clock used to stamp new notes: the system clock in the configured timezone, or a
+/// fixed instant for `--at`.
+fn clock_for_append(at: Option<&str>, parser: &NoteParser) -> Box<dyn Clock> {
+    let timezone = parser.timezone();
+    let Some(at) = at else {
+        return Box::new(timezone);
     };
-    let cfg = match RuntimeConfig::load_or_default() {
-        Ok(c) => c,
+    match parse_time_point(at, parser.pattern(), &timezone).and_then(|p| p.start(timezone)) {
+        Some(instant) => Box::new(FixedClock(instant)),
+        None => {
+            eprintln!("{}", QueryError::InvalidTime(at.to_string()));
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Reads `source` with `importer`, reports what it skipped and merges the notes into
+/// `store`.
+fn import_or_exit(store: &dyn NoteStore, importer: &dyn NoteImporter, source: &std::path::Path) {
+    let parsed = match std::fs::read_to_string(source).and_then(|input| importer.parse(&input)) {
+        Ok(parsed) => parsed,
         Err(e) => {
-            eprintln!("config load error: {e}");
+            eprintln!("import error: {}: {e}", source.display());
+            std::process::exit(1);
+        }
+    };
+    for (line, raw) in &parsed.skipped {
+        let first_line = raw.lines().next().unwrap_or_default();
+        eprintln!("line {line}: no timestamp, skipped: {first_line}");
+    }
+    let report = match merge_notes(store, parsed.notes) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("write error: {e}");
             std::process::exit(1);
         }
     };
+    let n = report.added;
+    print!(
+        "imported {n} note{} from {}",
+        if n == 1 { "" } else { "s" },
+        source.display()
+    );
+    match report.duplicates {
+        0 => println!(),
+        1 => println!(" (1 duplicate skipped)"),
+        d => println!(" ({d} duplicates skipped)"),
+    }
+}
+
+fn main() {
+    let invocation = match Cli::parse_invocation() {
+        Ok(v) => v,
+        Err(e) => {
+            let _ = e.print();
+            std::process::exit(2);
+        }
+    };
+    let action = invocation.action;
 
-    match action {
-        CommandAction::ShowConfigPath => match nt::config::default_config_file_path() {
+    if let CommandAction::ShowConfigPath = action {
+        match nt::config::config_file_in_use(invocation.config_file.as_deref()) {
             Ok(p) => {
                 println!("{}", p.display());
             }
@@ -29,45 +177,471 @@ fn main() {
                 eprintln!("config path resolve error: {e}");
                 std::process::exit(1);
             }
-        },
-        CommandAction::Append { text } => {
-            let clock = SystemClock;
-            if let Err(e) = append_note_line_to_file_with_clock(
-                &cfg.expanded_note_file_path,
-                &clock,
-                &cfg.datetime_format_pattern,
-                &text,
-            ) {
+        }
+        return;
+    }
+
+    if let CommandAction::ShowConfig { origin } = action {
+        let settings = match RuntimeConfig::load_with_origins(invocation.config_file.as_deref()) {
+            Ok((_, settings)) => settings,
+            Err(e) => {
+                eprintln!("config load error: {e}");
+                std::process::exit(1);
+            }
+        };
+        for setting in settings {
+            if origin {
+                println!("{} = {}  # {}", setting.key, setting.value, setting.origin);
+            } else {
+                println!("{} = {}", setting.key, setting.value);
+            }
+        }
+        return;
+    }
+
+    let loaded = match &invocation.config_file {
+        Some(p) => RuntimeConfig::load_from_explicit_path(p),
+        None => RuntimeConfig::load_or_default(),
+    };
+    let cfg = match loaded {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("config load error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let store = match cfg.note_store() {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("storage error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let store = store.as_ref();
+    match action {
+        CommandAction::ShowConfigPath | CommandAction::ShowConfig { .. } => {
+            unreachable!("handled before config load")
+        }
+        CommandAction::Append { text, at } => {
+            let clock = clock_for_append(at.as_deref(), store.parser());
+            if let Err(e) = store.append_note(clock.as_ref(), &text) {
                 eprintln!("write error: {e}");
                 std::process::exit(1);
             } else {
                 println!("added 1 note");
             }
         }
-        CommandAction::Print { count } => {
-            match nt::notes::collect_last_n_lines_from_file_allow_missing(
-                &cfg.expanded_note_file_path,
-                count,
-            ) {
-                Ok(Some(lines)) => {
-                    for l in lines {
-                        println!("{l}");
+        CommandAction::Print {
+            count,
+            filter,
+            output,
+        } => {
+            let parser = store.parser();
+            let filter = note_filter_or_exit(&filter, parser);
+            let display_zone = output.tz;
+            let output = note_output_or_exit(&output);
+            let entries = match count {
+                Some(count) => store.last_entries(count, &filter),
+                None => store.find_entries(&filter, None),
+            };
+            match entries {
+                Ok(Some(entries)) => {
+                    for entry in entries {
+                        let entry = entry.in_timezone(display_zone);
+                        match &output {
+                            Some(output) => println!("{}", output.render(&entry, parser.pattern())),
+                            None => println!("{} {}", entry.id(), entry.to_line(parser.pattern())),
+                        }
+                    }
+                }
+                Ok(None) => exit_no_notes(),
+                Err(e) => {
+                    eprintln!("read error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        CommandAction::Search {
+            pattern,
+            mode,
+            filter,
+            output,
+        } => {
+            let parser = store.parser();
+            let filter = note_filter_or_exit(&filter, parser);
+            let display_zone = output.tz;
+            let output = note_output_or_exit(&output);
+            let matcher = match NoteMatcher::new(&pattern, mode.mode()) {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("invalid search pattern: {e}");
+                    std::process::exit(2);
+                }
+            };
+            match store.find_entries(&filter, Some(&matcher)) {
+                Ok(Some(matches)) => {
+                    if mode.count {
+                        println!("{}", matches.len());
+                    } else {
+                        for entry in matches {
+                            let entry = entry.in_timezone(display_zone);
+                            match &output {
+                                Some(output) => {
+                                    println!("{}", output.render(&entry, parser.pattern()))
+                                }
+                                None => println!("{}", entry.to_line(parser.pattern())),
+                            }
+                        }
+                    }
+                }
+                Ok(None) => exit_no_notes(),
+                Err(e) => {
+                    eprintln!("read error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        CommandAction::ListTags { filter } => {
+            let parser = store.parser();
+            let filter = note_filter_or_exit(&filter, parser);
+            match store.find_entries(&filter, None) {
+                Ok(Some(selected)) => {
+                    for summary in summarize_tags(&selected) {
+                        println!(
+                            "#{}\t{}\t{}",
+                            summary.tag,
+                            summary.count,
+                            summary.last_used.format("%Y-%m-%d")
+                        );
+                    }
+                }
+                Ok(None) => exit_no_notes(),
+                Err(e) => {
+                    eprintln!("read error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        CommandAction::EditNote { selector, text } => {
+            let parser = store.parser();
+            let records = read_records_or_exit(store);
+            let entries: Vec<_> = records.iter().map(|r| parser.parse_line(r)).collect();
+            let index = match selector.resolve(&entries) {
+                Ok(i) => i,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+            let Some(note) = entries[index].as_note() else {
+                eprintln!("selected entry has no parseable timestamp; edit the file by hand");
+                std::process::exit(1);
+            };
+            let new_text = match text {
+                Some(t) => t,
+                None => match edit_note_text(&resolve_editor_command(), &note.text) {
+                    Ok(EditOutcome::Edited(t)) => t,
+                    Ok(EditOutcome::Unchanged) => {
+                        eprintln!("note unchanged");
+                        return;
+                    }
+                    Ok(EditOutcome::Empty) => {
+                        eprintln!("empty note; aborting");
+                        std::process::exit(2);
+                    }
+                    Err(e) => {
+                        eprintln!("editor error: {e}");
+                        std::process::exit(1);
+                    }
+                },
+            };
+            let (lock, mut records) = relock_records_or_exit(store, &records);
+            records[index] = parser
+                .replace_text(&records[index], &new_text)
+                .expect("entry was parsed above");
+            if let Err(e) = store.rewrite(&records) {
+                eprintln!("write error: {e}");
+                std::process::exit(1);
+            }
+            release_or_exit(lock);
+            println!("updated 1 note");
+        }
+        CommandAction::RemoveNotes {
+            selectors,
+            pattern,
+            yes,
+        } => {
+            let parser = store.parser();
+            let records = read_records_or_exit(store);
+            let entries: Vec<_> = records.iter().map(|r| parser.parse_line(r)).collect();
+            let mut indices = Vec::new();
+            for selector in &selectors {
+                match selector.resolve(&entries) {
+                    Ok(i) => indices.push(i),
+                    Err(e) => {
+                        eprintln!("{e}");
+                        std::process::exit(1);
                     }
                 }
+            }
+            if let Some(pattern) = &pattern {
+                let matcher = NoteMatcher::new(pattern, SearchMode::Substring)
+                    .expect("escaped pattern is a valid regex");
+                indices.extend((0..entries.len()).filter(|&i| matcher.is_match(&entries[i])));
+            }
+            indices.sort_unstable();
+            indices.dedup();
+            if indices.is_empty() {
+                eprintln!("no notes matched; nothing removed");
+                std::process::exit(1);
+            }
+            if !yes {
+                for &i in &indices {
+                    eprintln!("{}", entries[i].to_line(parser.pattern()));
+                }
+                let n = indices.len();
+                if !confirm(&format!(
+                    "remove {n} note{}?",
+                    if n == 1 { "" } else { "s" }
+                )) {
+                    eprintln!("aborted");
+                    std::process::exit(1);
+                }
+            }
+            let (lock, mut records) = relock_records_or_exit(store, &records);
+            let trash = trash_file_path(&cfg.expanded_note_file_path);
+            match move_records_to_trash(store, &trash, &mut records, &indices) {
+                Ok(removed) => {
+                    release_or_exit(lock);
+                    let n = removed.len();
+                    println!(
+                        "removed {n} note{} (restore with `nt undo`)",
+                        if n == 1 { "" } else { "s" }
+                    );
+                }
+                Err(e) => {
+                    eprintln!("write error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        CommandAction::Undo => {
+            match restore_last_trashed(store, &trash_file_path(&cfg.expanded_note_file_path)) {
+                Ok(Some(restored)) => {
+                    let n = restored.len();
+                    println!("restored {n} note{}", if n == 1 { "" } else { "s" });
+                }
                 Ok(None) => {
-                    println!("no notes have been made");
+                    eprintln!("nothing to undo");
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("undo error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        CommandAction::Migrate {
+            to,
+            output,
+            save_config,
+        } => {
+            let target_literal = match output {
+                Some(path) => match std::path::absolute(&path) {
+                    Ok(path) => path.to_string_lossy().into_owned(),
+                    Err(e) => {
+                        eprintln!("invalid output path: {e}");
+                        std::process::exit(2);
+                    }
+                },
+                None => std::path::Path::new(&cfg.configured_note_file_literal)
+                    .with_extension(to.extension())
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            let home_directory = dirs::home_dir().unwrap_or_else(|| "/".into());
+            let target_path = expand_leading_tilde_literal(&target_literal, &home_directory);
+            if target_path == cfg.expanded_note_file_path {
+                eprintln!(
+                    "{} is the current note file; choose another --output",
+                    target_path.display()
+                );
+                std::process::exit(2);
+            }
+            let target = match open_store(
+                target_path.clone(),
+                NoteParser::with_format(&cfg.datetime_format_pattern, to)
+                    .with_timezone(cfg.timezone),
+                cfg.durability,
+            ) {
+                Ok(target) => target,
+                Err(e) => {
+                    eprintln!("storage error: {e}");
+                    std::process::exit(1);
+                }
+            };
+            let report = match migrate_notes(store, target.as_ref()) {
+                Ok(Some(report)) => report,
+                Ok(None) => exit_no_notes(),
+                Err(e) => {
+                    eprintln!("migration error: {}: {e}", target_path.display());
+                    std::process::exit(1);
+                }
+            };
+            for (position, raw) in &report.unparsed {
+                let first_line = raw.lines().next().unwrap_or_default();
+                eprintln!("record {position}: unparseable timestamp, copied as is: {first_line}");
+            }
+            if !report.inexact.is_empty() {
+                let positions: Vec<String> =
+                    report.inexact.iter().map(ToString::to_string).collect();
+                eprintln!(
+                    "warning: {} cannot store these timestamps exactly: records {}",
+                    to.name(),
+                    positions.join(", ")
+                );
+            }
+            let n = report.migrated;
+            println!(
+                "migrated {n} note{} to {}",
+                if n == 1 { "" } else { "s" },
+                target_path.display()
+            );
+            if save_config {
+                let saved = RuntimeConfig::save_note_store(
+                    invocation.config_file.as_deref(),
+                    target_literal,
+                    to,
+                );
+                if let Err(e) = saved {
+                    eprintln!("config save error: {e}");
+                    std::process::exit(1);
+                }
+                println!("config now uses the {} store", to.name());
+            }
+        }
+        CommandAction::ImportLegacy {
+            path,
+            datetime_format,
+        } => {
+            let legacy = match legacy_config_file_path().map(|p| LegacyConfig::load(&p)) {
+                Some(Ok(Some(legacy))) => legacy,
+                Some(Ok(None)) | None => LegacyConfig::default(),
+                Some(Err(e)) => {
+                    eprintln!("legacy config read error: {e}");
+                    std::process::exit(1);
+                }
+            };
+            let source = path.unwrap_or_else(|| {
+                let home_directory = dirs::home_dir().unwrap_or_else(|| "/".into());
+                expand_leading_tilde_literal(legacy.note_file_literal(), &home_directory)
+            });
+            let pattern = datetime_format
+                .as_deref()
+                .unwrap_or(legacy.datetime_format_pattern());
+            let in_place = match (
+                std::fs::canonicalize(&source),
+                std::fs::canonicalize(&cfg.expanded_note_file_path),
+            ) {
+                (Ok(source), Ok(note_file)) => source == note_file,
+                _ => false,
+            };
+            if !in_place {
+                let importer = LegacyImporter {
+                    pattern: pattern.to_string(),
+                    timezone: store.parser().timezone(),
+                };
+                import_or_exit(store, &importer, &source);
+                return;
+            }
+            let report = match convert_legacy_in_place(store, pattern) {
+                Ok(report) => report.unwrap_or_default(),
+                Err(e) => {
+                    eprintln!("import error: {}: {e}", source.display());
+                    std::process::exit(1);
                 }
+            };
+            for (position, raw) in &report.unparsed {
+                eprintln!("line {position}: unparseable timestamp, kept as is: {raw}");
+            }
+            let n = report.imported;
+            println!(
+                "imported {n} note{} from {}",
+                if n == 1 { "" } else { "s" },
+                source.display()
+            );
+        }
+        CommandAction::ImportNotes { path, format } => {
+            let importer: Box<dyn NoteImporter> = match format {
+                ImportFormat::Jrnl { datetime_format } => Box::new(JrnlImporter {
+                    pattern: datetime_format,
+                    timezone: store.parser().timezone(),
+                }),
+                ImportFormat::Markdown => Box::new(MarkdownImporter {
+                    pattern: cfg.datetime_format_pattern.clone(),
+                    timezone: store.parser().timezone(),
+                }),
+                ImportFormat::Csv {
+                    timestamp_column,
+                    text_column,
+                    timestamp_format,
+                    delimiter,
+                } => Box::new(CsvImporter {
+                    timestamp_column,
+                    text_column,
+                    timestamp_format,
+                    pattern: cfg.datetime_format_pattern.clone(),
+                    delimiter: delimiter as u8,
+                    timezone: store.parser().timezone(),
+                }),
+            };
+            import_or_exit(store, importer.as_ref(), &path);
+        }
+        CommandAction::Export {
+            format,
+            filter,
+            output,
+        } => {
+            use std::io::{BufWriter, Write};
+            let filter = note_filter_or_exit(&filter, store.parser());
+            let entries = match store.find_entries(&filter, None) {
+                Ok(Some(entries)) => entries,
+                Ok(None) => exit_no_notes(),
                 Err(e) => {
                     eprintln!("read error: {e}");
                     std::process::exit(1);
                 }
+            };
+            let written = match &output {
+                Some(path) => std::fs::File::create(path).and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    write_export(&mut writer, format, &entries)?;
+                    writer.flush()
+                }),
+                None => {
+                    let mut stdout = std::io::stdout().lock();
+                    write_export(&mut stdout, format, &entries).and_then(|()| stdout.flush())
+                }
+            };
+            if let Err(e) = written {
+                eprintln!("write error: {e}");
+                std::process::exit(1);
+            }
+            if let Some(path) = output {
+                let n = entries.len();
+                println!(
+                    "exported {n} note{} to {}",
+                    if n == 1 { "" } else { "s" },
+                    path.display()
+                );
             }
         }
-        CommandAction::AppendFromStdin => {
+        CommandAction::AppendFromStdin { at } => {
             use std::io::{BufRead, BufReader};
             let stdin = std::io::stdin();
             let reader = BufReader::new(stdin.lock());
-            let clock = SystemClock;
+            let clock = clock_for_append(at.as_deref(), store.parser());
             let mut added = 0usize;
             for line_result in reader.lines() {
                 match line_result {
@@ -79,12 +653,7 @@ fn main() {
                         if line.trim().is_empty() {
                             continue;
                         }
-                        if let Err(e) = append_note_line_to_file_with_clock(
-                            &cfg.expanded_note_file_path,
-                            &clock,
-                            &cfg.datetime_format_pattern,
-                            &line,
-                        ) {
+                        if let Err(e) = store.append_note(clock.as_ref(), &line) {
                             eprintln!("write error: {e}");
                             std::process::exit(1);
                         }
@@ -103,18 +672,58 @@ fn main() {
                 println!("added {added} note{}", if added == 1 { "" } else { "s" });
             }
         }
-        CommandAction::InteractiveAppend => {
+        CommandAction::AppendMultilineFromStdin { at } => {
+            use std::io::{IsTerminal, Read};
+            let mut stdin = std::io::stdin();
+            if stdin.is_terminal() {
+                eprintln!("enter note, finish with Ctrl-D:");
+            }
+            let mut buffer = String::new();
+            if let Err(e) = stdin.read_to_string(&mut buffer) {
+                eprintln!("stdin read error: {e}");
+                std::process::exit(1);
+            }
+            let Some(text) = normalize_note_body(&buffer) else {
+                eprintln!("note text cannot be empty");
+                std::process::exit(2);
+            };
+            let clock = clock_for_append(at.as_deref(), store.parser());
+            if let Err(e) = store.append_note(clock.as_ref(), &text) {
+                eprintln!("write error: {e}");
+                std::process::exit(1);
+            }
+            println!("added 1 note");
+        }
+        CommandAction::ComposeInEditor { at } => {
+            let text = match edit_note_text(&resolve_editor_command(), "") {
+                Ok(EditOutcome::Edited(text)) => text,
+                Ok(EditOutcome::Empty | EditOutcome::Unchanged) => {
+                    eprintln!("empty note; aborting");
+                    std::process::exit(2);
+                }
+                Err(e) => {
+                    eprintln!("editor error: {e}");
+                    std::process::exit(1);
+                }
+            };
+            let clock = clock_for_append(at.as_deref(), store.parser());
+            if let Err(e) = store.append_note(clock.as_ref(), &text) {
+                eprintln!("write error: {e}");
+                std::process::exit(1);
+            }
+            println!("added 1 note");
+        }
+        CommandAction::InteractiveAppend { at } => {
             use std::io::{BufReader, IsTerminal, stdin, stdout};
-            let clock = SystemClock;
+            let clock = clock_for_append(at.as_deref(), store.parser());
             let mut reader = BufReader::new(stdin());
             let prompt_enabled = stdout().is_terminal();
             match run_interactive_session(
                 &mut reader,
                 stdout(),
                 prompt_enabled,
-                &clock,
-                &cfg.datetime_format_pattern,
-                &cfg.expanded_note_file_path,
+                clock.as_ref(),
+                store,
             ) {
                 Ok(InteractiveOutcome::Added(n)) => {
                     println!("added {n} note{}", if n == 1 { "" } else { "s" });
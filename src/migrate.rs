@@ -0,0 +1,67 @@
+use std::io;
+
+use crate::notes::NoteEntry;
+use crate::store::NoteStore;
+
+/// What [`migrate_notes`] wrote and what it could not convert.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Records written to the target store.
+    pub migrated: usize,
+    /// Records whose timestamp did not parse, by 1-based position in the source, with
+    /// their raw contents. They are copied across unchanged.
+    pub unparsed: Vec<(usize, String)>,
+    /// 1-based positions of notes whose timestamp the target cannot store exactly, e.g.
+    /// seconds dropped by a text pattern without `%S`.
+    pub inexact: Vec<usize>,
+}
+
+/// Copies every record of `source` into `target`, converting parsed notes to the
+/// target's format and keeping unparsed records as they are.
+///
+/// Both stores are locked for the duration, and `target` is written with a single
+/// [`NoteStore::rewrite`], so it ends up either complete or untouched. Fails with
+/// [`io::ErrorKind::AlreadyExists`] when `target` already holds notes, and returns
+/// None when `source` has none.
+pub fn migrate_notes<S, T>(source: &S, target: &T) -> io::Result<Option<MigrationReport>>
+where
+    S: NoteStore + ?Sized,
+    T: NoteStore + ?Sized,
+{
+    let source_lock = source.lock()?;
+    let Some(entries) = source.entries()? else {
+        return Ok(None);
+    };
+    let target_lock = target.lock()?;
+    if target.records()?.is_some_and(|mut r| r.next().is_some()) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "migration target already contains notes",
+        ));
+    }
+
+    let parser = target.parser();
+    let mut report = MigrationReport::default();
+    let mut records = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.into_iter().enumerate() {
+        let record = match entry {
+            NoteEntry::Parsed(note) => {
+                let record = parser.format_note(&note);
+                if parser.parse_line(&record).as_note() != Some(&note) {
+                    report.inexact.push(index + 1);
+                }
+                record
+            }
+            NoteEntry::Unparsed(raw) => {
+                report.unparsed.push((index + 1, raw.clone()));
+                raw
+            }
+        };
+        records.push(record);
+    }
+    target.rewrite(&records)?;
+    target_lock.release()?;
+    source_lock.release()?;
+    report.migrated = records.len();
+    Ok(Some(report))
+}
@@ -1,49 +1,710 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fs::{self, OpenOptions};
-use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
-use crate::time::Clock;
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use serde::{Deserialize, Serialize};
 
-pub fn append_note_line_to_file(path: &Path, timestamp: &str, text: &str) -> io::Result<()> {
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            fs::create_dir_all(parent)?;
+use crate::lock::{LOCK_TIMEOUT, NoteLock};
+use crate::time::{Clock, Timezone, parse_time_point_prefix, pattern_records_offset};
+
+/// A note read back from the note file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub timestamp: DateTime<FixedOffset>,
+    pub text: String,
+    /// Lowercased `#tag` names found in `text`, without the `#`, in order of first use.
+    pub tags: Vec<String>,
+    /// Short stable identifier derived from the timestamp and text (see [`note_id`]).
+    pub id: String,
+}
+
+/// One record of the note file: either a note whose timestamp parsed under the
+/// configured pattern, or the raw line when it did not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteEntry {
+    Parsed(Note),
+    Unparsed(String),
+}
+
+impl NoteEntry {
+    pub fn as_note(&self) -> Option<&Note> {
+        match self {
+            NoteEntry::Parsed(note) => Some(note),
+            NoteEntry::Unparsed(_) => None,
         }
     }
-    let file = OpenOptions::new().create(true).append(true).open(path)?;
-    let mut buf_writer = BufWriter::new(file);
-    append_note_line_to_writer(&mut buf_writer, timestamp, text)
+
+    /// The note's ID; unparsed lines get an ID derived from their raw contents.
+    pub fn id(&self) -> String {
+        match self {
+            NoteEntry::Parsed(note) => note.id.clone(),
+            NoteEntry::Unparsed(raw) => hex_id(fnv1a_64(raw.as_bytes())),
+        }
+    }
+
+    /// The entry with its timestamp shown in `timezone`, when one is given. The instant
+    /// and the note's ID are unchanged.
+    pub fn in_timezone(self, timezone: Option<Timezone>) -> Self {
+        match (self, timezone) {
+            (NoteEntry::Parsed(mut note), Some(timezone)) => {
+                note.timestamp = timezone.convert(note.timestamp);
+                NoteEntry::Parsed(note)
+            }
+            (entry, _) => entry,
+        }
+    }
+
+    /// Renders the entry as a plain-text line under `pattern`, continuation lines included.
+    /// This is how entries are displayed whatever the storage format.
+    pub fn to_line(&self, pattern: &str) -> String {
+        match self {
+            NoteEntry::Parsed(note) => note.to_line(pattern),
+            NoteEntry::Unparsed(raw) => encode_continuation_lines(raw),
+        }
+    }
+}
+
+impl Note {
+    pub fn new(timestamp: DateTime<FixedOffset>, text: String) -> Self {
+        let tags = extract_tags(&text);
+        let id = note_id(&timestamp, &text);
+        Self {
+            timestamp,
+            text,
+            tags,
+            id,
+        }
+    }
+
+    pub fn to_line(&self, pattern: &str) -> String {
+        encode_continuation_lines(&format!("{} {}", self.timestamp.format(pattern), self.text))
+    }
+}
+
+/// Number of hex digits in a note ID.
+pub const NOTE_ID_LEN: usize = 8;
+
+/// Derives a note's ID from its wall-clock timestamp (to the second) and its text.
+///
+/// The ID does not depend on the timezone the file is read in or on the configured
+/// datetime pattern, so it stays the same across machines and storage formats.
+pub fn note_id(timestamp: &DateTime<FixedOffset>, text: &str) -> String {
+    let key = format!(
+        "{}\0{}",
+        timestamp.naive_local().format("%Y-%m-%dT%H:%M:%S"),
+        text
+    );
+    hex_id(fnv1a_64(key.as_bytes()))
+}
+
+fn hex_id(hash: u64) -> String {
+    let mut id = format!("{hash:016x}");
+    id.truncate(NOTE_ID_LEN);
+    id
 }
 
-pub fn append_note_line_to_file_with_clock<C: Clock>(
+/// 64-bit FNV-1a: tiny, and stable across Rust versions and platforms.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Prefix that marks a physical line as the continuation of the note above it.
+///
+/// A multi-line note is stored as `"<timestamp> <first line>"` followed by one
+/// `"\t<line>"` per further line, so a single note never looks like several.
+pub const CONTINUATION_PREFIX: char = '\t';
+
+/// Encodes embedded newlines of a logical record as continuation lines (no trailing newline).
+pub fn encode_continuation_lines(record: &str) -> String {
+    let mut encoded = String::with_capacity(record.len());
+    for (i, line) in record.split('\n').enumerate() {
+        if i > 0 {
+            encoded.push('\n');
+            encoded.push(CONTINUATION_PREFIX);
+        }
+        encoded.push_str(line.strip_suffix('\r').unwrap_or(line));
+    }
+    encoded
+}
+
+/// Prepares free-form multi-line input as note text: strips carriage returns and
+/// leading/trailing blank lines. Returns None when nothing but whitespace remains.
+pub fn normalize_note_body(raw: &str) -> Option<String> {
+    let lines: Vec<&str> = raw
+        .lines()
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .collect();
+    let first = lines.iter().position(|l| !l.trim().is_empty())?;
+    let last = lines.iter().rposition(|l| !l.trim().is_empty())?;
+    Some(lines[first..=last].join("\n"))
+}
+
+/// Iterator over logical records of a note file: each record is one physical line plus
+/// any continuation lines after it, joined with `\n` and with the prefixes removed.
+pub struct LogicalLines<R> {
+    lines: io::Lines<R>,
+    pending: Option<String>,
+}
+
+pub fn logical_lines<R: BufRead>(reader: R) -> LogicalLines<R> {
+    LogicalLines {
+        lines: reader.lines(),
+        pending: None,
+    }
+}
+
+impl<R: BufRead> Iterator for LogicalLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.lines.next() {
+                Some(Ok(line)) => match (line.strip_prefix(CONTINUATION_PREFIX), &mut self.pending)
+                {
+                    (Some(continuation), Some(record)) => {
+                        record.push('\n');
+                        record.push_str(continuation);
+                    }
+                    // A continuation with nothing above it is kept verbatim as its own record.
+                    _ => {
+                        if let Some(record) = self.pending.replace(line) {
+                            return Some(Ok(record));
+                        }
+                    }
+                },
+                Some(Err(e)) => return Some(Err(e)),
+                None => return self.pending.take().map(Ok),
+            }
+        }
+    }
+}
+
+/// Extracts `#tag` tokens from note text.
+///
+/// A tag starts at the beginning of a word (so `C#` or `url/#anchor` are not tags), must
+/// begin with a letter (so `#42` issue references are not tags), and continues over
+/// letters, digits, `_`, `-` and `/`. Tags are lowercased and deduplicated.
+pub fn extract_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        let at_word_start = previous.is_none_or(|p| p.is_whitespace() || "([{\"'".contains(p));
+        previous = Some(c);
+        if c != '#' || !at_word_start {
+            continue;
+        }
+        let rest = &text[i + 1..];
+        if !rest.starts_with(|ch: char| ch.is_alphabetic()) {
+            continue;
+        }
+        let end = rest
+            .find(|ch: char| !(ch.is_alphanumeric() || "_-/".contains(ch)))
+            .unwrap_or(rest.len());
+        let tag = normalize_tag(rest[..end].trim_end_matches(['-', '/']));
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Canonical form of a tag as given by a user, with or without the leading `#`.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+/// Usage statistics for one tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSummary {
+    pub tag: String,
+    pub count: usize,
+    pub last_used: DateTime<FixedOffset>,
+}
+
+/// Summarizes tag usage across `entries`, most used first (ties broken by name).
+pub fn summarize_tags<'a, I: IntoIterator<Item = &'a NoteEntry>>(entries: I) -> Vec<TagSummary> {
+    let mut by_tag: HashMap<&str, TagSummary> = HashMap::new();
+    for note in entries.into_iter().filter_map(NoteEntry::as_note) {
+        for tag in &note.tags {
+            let summary = by_tag.entry(tag).or_insert_with(|| TagSummary {
+                tag: tag.clone(),
+                count: 0,
+                last_used: note.timestamp,
+            });
+            summary.count += 1;
+            summary.last_used = summary.last_used.max(note.timestamp);
+        }
+    }
+    let mut summaries: Vec<TagSummary> = by_tag.into_values().collect();
+    summaries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    summaries
+}
+
+/// How notes are laid out in the note file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// `"<timestamp> <text>"` lines with the configured datetime pattern.
+    #[default]
+    Text,
+    /// One JSON object per line: `{"ts": RFC 3339, "text", "tags", "id"}`.
+    Jsonl,
+    /// A SQLite database (needs the `sqlite` feature). Its records are exchanged as
+    /// JSON Lines objects.
+    Sqlite,
+}
+
+impl StorageFormat {
+    /// The name used for this format in `nt.toml` and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            StorageFormat::Text => "text",
+            StorageFormat::Jsonl => "jsonl",
+            StorageFormat::Sqlite => "sqlite",
+        }
+    }
+
+    /// The conventional file extension for a note file in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            StorageFormat::Text => "txt",
+            StorageFormat::Jsonl => "jsonl",
+            StorageFormat::Sqlite => "db",
+        }
+    }
+}
+
+/// A note as stored in a JSON Lines file. `tags` and `id` are written for other tools;
+/// on reading they are derived from `ts` and `text` again, like for text records.
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonRecord {
+    ts: String,
+    text: String,
+    #[serde(default, skip_deserializing)]
+    tags: Vec<String>,
+    #[serde(default, skip_deserializing)]
+    id: String,
+}
+
+impl JsonRecord {
+    fn from_note(note: &Note) -> Self {
+        Self {
+            ts: note.timestamp.to_rfc3339_opts(SecondsFormat::Secs, false),
+            text: note.text.clone(),
+            tags: note.tags.clone(),
+            id: note.id.clone(),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("string fields always serialize")
+    }
+}
+
+/// Parses and writes records of the note file in its [`StorageFormat`].
+///
+/// Text records are `"<timestamp> <text>"` lines written under a given datetime
+/// pattern. Patterns without an offset are interpreted in the parser's timezone
+/// (local unless set with [`NoteParser::with_timezone`]); patterns without a time of
+/// day resolve to midnight. JSON Lines records carry
+/// their own RFC 3339 timestamps, and the pattern is only used for display.
+#[derive(Debug, Clone)]
+pub struct NoteParser {
+    pattern: String,
+    format: StorageFormat,
+    timezone: Timezone,
+    /// Whether text timestamps keep their own offset rather than being written as wall
+    /// clock time in `timezone`.
+    records_offset: bool,
+}
+
+impl NoteParser {
+    pub fn new(pattern: &str) -> Self {
+        Self::with_format(pattern, StorageFormat::Text)
+    }
+
+    pub fn with_format(pattern: &str, format: StorageFormat) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            format,
+            timezone: Timezone::Local,
+            records_offset: pattern_records_offset(pattern),
+        }
+    }
+
+    /// Reads text timestamps without an offset in `timezone`.
+    pub fn with_timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn format(&self) -> StorageFormat {
+        self.format
+    }
+
+    pub fn timezone(&self) -> Timezone {
+        self.timezone
+    }
+
+    /// Builds the record for a new note timestamped by `clock`.
+    pub fn new_record<C: Clock + ?Sized>(&self, clock: &C, text: &str) -> String {
+        match self.format {
+            StorageFormat::Text => format!("{} {text}", self.text_timestamp(clock.now())),
+            StorageFormat::Jsonl | StorageFormat::Sqlite => {
+                JsonRecord::from_note(&Note::new(clock.now(), text.to_string())).to_line()
+            }
+        }
+    }
+
+    /// Builds the record for an existing note, e.g. when converting between formats.
+    pub fn format_note(&self, note: &Note) -> String {
+        match self.format {
+            StorageFormat::Text => {
+                format!("{} {}", self.text_timestamp(note.timestamp), note.text)
+            }
+            StorageFormat::Jsonl | StorageFormat::Sqlite => JsonRecord::from_note(note).to_line(),
+        }
+    }
+
+    /// Writes `timestamp` under the pattern so that it reads back as the same instant:
+    /// patterns without an offset get the wall clock time in the parser's timezone.
+    fn text_timestamp(&self, timestamp: DateTime<FixedOffset>) -> String {
+        let timestamp = if self.records_offset {
+            timestamp
+        } else {
+            self.timezone.convert(timestamp)
+        };
+        timestamp.format(&self.pattern).to_string()
+    }
+
+    /// Replaces the text of a parsed record while keeping its timestamp exactly as written.
+    /// Returns None when the record has no parseable timestamp.
+    pub fn replace_text(&self, record: &str, new_text: &str) -> Option<String> {
+        let NoteEntry::Parsed(note) = self.parse_line(record) else {
+            return None;
+        };
+        match self.format {
+            StorageFormat::Text => {
+                let timestamp_and_separator = &record[..record.len() - note.text.len()];
+                Some(format!("{timestamp_and_separator}{new_text}"))
+            }
+            StorageFormat::Jsonl | StorageFormat::Sqlite => {
+                let mut stored: JsonRecord = serde_json::from_str(record).ok()?;
+                let updated = Note::new(note.timestamp, new_text.to_string());
+                stored.text = updated.text;
+                stored.tags = updated.tags;
+                stored.id = updated.id;
+                Some(stored.to_line())
+            }
+        }
+    }
+
+    pub fn parse_line(&self, line: &str) -> NoteEntry {
+        match self.format {
+            StorageFormat::Text => self.parse_text_line(line),
+            StorageFormat::Jsonl | StorageFormat::Sqlite => {
+                serde_json::from_str::<JsonRecord>(line)
+                    .ok()
+                    .and_then(|r| {
+                        Some(Note::new(DateTime::parse_from_rfc3339(&r.ts).ok()?, r.text))
+                    })
+                    .map_or_else(|| NoteEntry::Unparsed(line.to_string()), NoteEntry::Parsed)
+            }
+        }
+    }
+
+    fn parse_text_line(&self, line: &str) -> NoteEntry {
+        match parse_time_point_prefix(line, &self.pattern, self.timezone) {
+            Some((point, remainder)) if remainder.is_empty() || remainder.starts_with(' ') => {
+                match point.start(self.timezone) {
+                    Some(timestamp) => NoteEntry::Parsed(Note::new(
+                        timestamp,
+                        remainder.strip_prefix(' ').unwrap_or(remainder).to_string(),
+                    )),
+                    None => NoteEntry::Unparsed(line.to_string()),
+                }
+            }
+            _ => NoteEntry::Unparsed(line.to_string()),
+        }
+    }
+}
+
+/// How hard an append works to get a note onto stable storage before returning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Durability {
+    /// Leave buffered data to be written when the file is closed; write errors there go unreported.
+    None,
+    /// Hand the note to the operating system and report any write error.
+    #[default]
+    Flush,
+    /// Also `fsync` the note file (and its directory when the file is first created),
+    /// so the note survives a crash or power loss.
+    Fsync,
+}
+
+/// Appends one text-format note while holding the [`NoteLock`].
+pub fn append_note_line_to_file(
     path: &Path,
-    clock: &C,
-    pattern: &str,
+    timestamp: &str,
     text: &str,
+    durability: Durability,
 ) -> io::Result<()> {
-    let ts = clock.now_formatted(pattern);
-    append_note_line_to_file(path, &ts, text)
+    append_record_to_file(path, &format!("{timestamp} {text}"), durability)
+}
+
+/// Appends one record while holding the [`NoteLock`], so concurrent writers never interleave.
+pub fn append_record_to_file(path: &Path, record: &str, durability: Durability) -> io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let _lock = NoteLock::acquire(path, LOCK_TIMEOUT)?;
+    let created = !path.exists();
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut buf_writer = BufWriter::new(file);
+    buf_writer.write_all(encode_continuation_lines(record).as_bytes())?;
+    buf_writer.write_all(b"\n")?;
+    match durability {
+        Durability::None => Ok(()),
+        Durability::Flush => buf_writer.flush(),
+        Durability::Fsync => {
+            let file = buf_writer.into_inner().map_err(|e| e.into_error())?;
+            file.sync_data()?;
+            if created {
+                sync_parent_directory(path)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Makes a newly created or renamed directory entry for `path` durable.
+#[cfg(unix)]
+fn sync_parent_directory(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::File::open(parent)?.sync_all(),
+        _ => fs::File::open(".")?.sync_all(),
+    }
+}
+
+/// Directories cannot be opened for syncing here; renames are durable once the
+/// file system commits them.
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Reads every logical record of the note file (see [`logical_lines`]).
+pub fn read_records_from_file(path: &Path) -> io::Result<Vec<String>> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    logical_lines(BufReader::new(file)).collect()
 }
 
+/// Replaces the note file with `records` via [`rewrite_file_atomically`].
+/// Callers must hold the [`NoteLock`] from before they read `records`.
+pub fn rewrite_records_to_file(path: &Path, records: &[String]) -> io::Result<()> {
+    rewrite_file_atomically(path, |writer| {
+        for record in records {
+            writer.write_all(encode_continuation_lines(record).as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    })
+}
+
+/// Replaces the file at `path` with whatever `write_contents` writes, crash-safely:
+/// the new contents go to a temporary file in the same directory, which is fsynced and
+/// then renamed over the original, and the rename itself is made durable. A crash at any
+/// point leaves either the old file or the new one, never a mix. Permissions of an
+/// existing file are kept.
+pub fn rewrite_file_atomically<F>(path: &Path, write_contents: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<fs::File>) -> io::Result<()>,
+{
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".tmp-{}", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temp_path)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        let mut writer = BufWriter::new(file);
+        write_contents(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        sync_parent_directory(path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Block size used when reading a note file backwards from its end.
+pub const TAIL_BLOCK_SIZE: usize = 64 * 1024;
+
 pub fn collect_last_n_lines_from_file(path: &Path, count: usize) -> io::Result<Vec<String>> {
     let file = OpenOptions::new().read(true).open(path)?;
-    let reader = BufReader::new(file);
-    collect_last_n_lines_from_reader(reader, count)
+    collect_last_n_lines_backwards(file, count, TAIL_BLOCK_SIZE)
 }
 
-/// Attempts to collect the last N lines, but returns Ok(None) if the file does not exist.
-pub fn collect_last_n_lines_from_file_allow_missing(
-    path: &Path,
+/// Collects the last `count` logical records like [`collect_last_n_lines_from_reader`], but
+/// reads `block_size` chunks backwards from the end and stops as soon as `count` complete
+/// records have been seen, so the cost does not grow with the size of the file.
+pub fn collect_last_n_lines_backwards<R: Read + Seek>(
+    reader: R,
     count: usize,
-) -> io::Result<Option<Vec<String>>> {
-    match collect_last_n_lines_from_file(path, count) {
-        Ok(lines) => Ok(Some(lines)),
+    block_size: usize,
+) -> io::Result<Vec<String>> {
+    let mut records = logical_lines_rev(reader, block_size)?
+        .take(count)
+        .collect::<io::Result<Vec<_>>>()?;
+    records.reverse();
+    Ok(records)
+}
+
+/// Iterator over the logical records of a note file from last to first (see
+/// [`logical_lines`]). It reads `block_size` chunks backwards from the end of the file, and
+/// only as many as are needed to complete the records taken so far.
+pub struct LogicalLinesRev<R> {
+    reader: R,
+    block_size: u64,
+    /// Offset of the first byte of `buf` in the file.
+    pos: u64,
+    /// Bytes from `pos` up to the start of the last record handed out.
+    buf: Vec<u8>,
+    /// `buf[..scan_end]` has not yet been searched for the start of a record.
+    scan_end: usize,
+    /// Records of the chunk being handed out, oldest first.
+    pending: Vec<String>,
+    done: bool,
+}
+
+pub fn logical_lines_rev<R: Read + Seek>(
+    mut reader: R,
+    block_size: usize,
+) -> io::Result<LogicalLinesRev<R>> {
+    let pos = reader.seek(SeekFrom::End(0))?;
+    Ok(LogicalLinesRev {
+        reader,
+        block_size: block_size.max(1) as u64,
+        pos,
+        buf: Vec::new(),
+        scan_end: 0,
+        pending: Vec::new(),
+        done: false,
+    })
+}
+
+impl<R: Read + Seek> LogicalLinesRev<R> {
+    /// Splits off the bytes from the last record start in `buf`, reading further back as
+    /// needed. Returns None once everything has been handed out.
+    fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            // A record starts after a newline, unless its line is a continuation line.
+            let start = (1..self.scan_end)
+                .rev()
+                .find(|&i| self.buf[i - 1] == b'\n' && self.buf[i] != CONTINUATION_PREFIX as u8);
+            if let Some(start) = start {
+                self.scan_end = start;
+                return Ok(Some(self.buf.split_off(start)));
+            }
+            if self.pos == 0 {
+                self.scan_end = 0;
+                return Ok((!self.buf.is_empty()).then(|| std::mem::take(&mut self.buf)));
+            }
+            let read_len = self.block_size.min(self.pos);
+            self.pos -= read_len;
+            let mut block = vec![0u8; read_len as usize];
+            self.reader.seek(SeekFrom::Start(self.pos))?;
+            self.reader.read_exact(&mut block)?;
+            // Only the new bytes and the old first byte (whose predecessor is now known)
+            // still need searching.
+            self.scan_end = (block.len() + 1).min(block.len() + self.buf.len());
+            block.extend_from_slice(&self.buf);
+            self.buf = block;
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for LogicalLinesRev<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.done {
+            match self.next_chunk() {
+                // Usually one record; orphan continuation lines at the start of the file
+                // can make several.
+                Ok(Some(chunk)) => match logical_lines(&chunk[..]).collect() {
+                    Ok(records) => self.pending = records,
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                },
+                Ok(None) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.pending.pop().map(Ok)
+    }
+}
+
+pub fn collect_last_n_notes_from_reader<R: BufRead>(
+    reader: R,
+    count: usize,
+    parser: &NoteParser,
+) -> io::Result<Vec<NoteEntry>> {
+    let lines = collect_last_n_lines_from_reader(reader, count)?;
+    Ok(lines.iter().map(|l| parser.parse_line(l)).collect())
+}
+
+pub fn read_all_notes_from_reader<R: BufRead>(
+    reader: R,
+    parser: &NoteParser,
+) -> io::Result<Vec<NoteEntry>> {
+    logical_lines(reader)
+        .map(|line_result| line_result.map(|l| parser.parse_line(&l)))
+        .collect()
+}
+
+/// Reads every note in the file, returning Ok(None) if the file does not exist.
+pub fn read_all_notes_from_file_allow_missing(
+    path: &Path,
+    parser: &NoteParser,
+) -> io::Result<Option<Vec<NoteEntry>>> {
+    match OpenOptions::new().read(true).open(path) {
+        Ok(file) => read_all_notes_from_reader(BufReader::new(file), parser).map(Some),
         Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
         Err(e) => Err(e),
     }
 }
 
+/// Collects the last `count` logical records (see [`logical_lines`]).
 pub fn collect_last_n_lines_from_reader<R: BufRead>(
     reader: R,
     count: usize,
@@ -52,7 +713,7 @@ pub fn collect_last_n_lines_from_reader<R: BufRead>(
         return Ok(Vec::new());
     }
     let mut deque: VecDeque<String> = VecDeque::with_capacity(count);
-    for line_result in reader.lines() {
+    for line_result in logical_lines(reader) {
         let line = line_result?;
         if deque.len() == count {
             deque.pop_front();
@@ -67,9 +728,8 @@ pub fn append_note_line_to_writer<W: Write>(
     timestamp: &str,
     text: &str,
 ) -> io::Result<()> {
-    writer.write_all(timestamp.as_bytes())?;
-    writer.write_all(b" ")?;
-    writer.write_all(text.as_bytes())?;
+    let record = encode_continuation_lines(&format!("{timestamp} {text}"));
+    writer.write_all(record.as_bytes())?;
     writer.write_all(b"\n")?;
     Ok(())
 }
@@ -0,0 +1,194 @@
+//! Machine-readable output for the commands that print notes.
+
+use clap::Args;
+use thiserror::Error;
+
+use crate::export::ExportRecord;
+use crate::notes::NoteEntry;
+use crate::time::Timezone;
+
+/// `--json`, `--format` and `--tz`, shared by `--print` and search.
+#[derive(Args, Debug, Clone, Default)]
+pub struct OutputArgs {
+    #[arg(
+        long = "json",
+        conflicts_with_all = ["format", "count"],
+        help = "print one JSON object per note: {\"ts\", \"text\", \"tags\", \"id\"}"
+    )]
+    pub json: bool,
+    #[arg(
+        long = "format",
+        value_name = "TEMPLATE",
+        conflicts_with = "count",
+        help = "print each note on one line with TEMPLATE, e.g. '{ts}\\t{text}'; fields: {id} {ts} {time} {text} {tags}"
+    )]
+    pub format: Option<String>,
+    #[arg(
+        long = "tz",
+        value_name = "ZONE",
+        help = "show timestamps in ZONE: UTC, local or an IANA name such as America/New_York"
+    )]
+    pub tz: Option<Timezone>,
+}
+
+impl OutputArgs {
+    pub fn is_empty(&self) -> bool {
+        !self.json && self.format.is_none() && self.tz.is_none()
+    }
+
+    /// The requested output, or None for the commands' usual plain lines.
+    pub fn note_output(&self) -> Result<Option<NoteOutput>, TemplateError> {
+        if self.json {
+            return Ok(Some(NoteOutput::Json));
+        }
+        self.format
+            .as_deref()
+            .map(|t| OutputTemplate::parse(t).map(NoteOutput::Template))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("unknown field {{{0}}} in --format (expected id, ts, time, text or tags)")]
+    UnknownField(String),
+    #[error("unmatched brace in --format; write {{{{ or }}}} for a literal brace")]
+    UnmatchedBrace,
+}
+
+/// How to print each note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteOutput {
+    /// JSON Lines, with the fields of [`ExportRecord`].
+    Json,
+    Template(OutputTemplate),
+}
+
+impl NoteOutput {
+    /// One line of output for `entry`, without the trailing newline. `pattern` is the
+    /// configured datetime pattern, used for `{time}`.
+    pub fn render(&self, entry: &NoteEntry, pattern: &str) -> String {
+        match self {
+            NoteOutput::Json => serde_json::to_string(&ExportRecord::new(entry))
+                .expect("export records always serialize"),
+            NoteOutput::Template(template) => template.render(entry, pattern),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    Ts,
+    Time,
+    Text,
+    Tags,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(Field),
+}
+
+/// A `--format` template: `{field}` placeholders, `{{`/`}}` for literal braces, and
+/// `\t`, `\n` and `\\` escapes so that shell-quoted templates work as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    parts: Vec<Part>,
+}
+
+impl OutputTemplate {
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::UnmatchedBrace),
+                        }
+                    }
+                    let field = match name.as_str() {
+                        "id" => Field::Id,
+                        "ts" => Field::Ts,
+                        "time" => Field::Time,
+                        "text" => Field::Text,
+                        "tags" => Field::Tags,
+                        _ => return Err(TemplateError::UnknownField(name)),
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => return Err(TemplateError::UnmatchedBrace),
+                '\\' => match chars.peek() {
+                    Some('t') => {
+                        chars.next();
+                        literal.push('\t');
+                    }
+                    Some('n') => {
+                        chars.next();
+                        literal.push('\n');
+                    }
+                    Some('\\') => {
+                        chars.next();
+                        literal.push('\\');
+                    }
+                    _ => literal.push('\\'),
+                },
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Fills in the template for `entry`. Entries without a parseable timestamp have
+    /// empty `{ts}`, `{time}` and `{tags}`, and their raw contents as `{text}`. `{text}`,
+    /// `{time}` and `{tags}` are written with the template's own `\t`, `\n` and `\\`
+    /// escapes, so a multi-line note still renders on one line.
+    pub fn render(&self, entry: &NoteEntry, pattern: &str) -> String {
+        let record = ExportRecord::new(entry);
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Field(Field::Id) => out.push_str(&record.id),
+                Part::Field(Field::Ts) => out.push_str(record.ts.as_deref().unwrap_or_default()),
+                Part::Field(Field::Time) => {
+                    if let Some(note) = entry.as_note() {
+                        push_escaped(&mut out, &note.timestamp.format(pattern).to_string());
+                    }
+                }
+                Part::Field(Field::Text) => push_escaped(&mut out, record.text),
+                Part::Field(Field::Tags) => {
+                    let tags: Vec<String> = record.tags.iter().map(|t| format!("#{t}")).collect();
+                    push_escaped(&mut out, &tags.join(" "));
+                }
+            }
+        }
+        out
+    }
+}
+
+fn push_escaped(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\\' => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::{Bound, RangeBounds};
+
+use chrono::{DateTime, Days, FixedOffset};
+use clap::Args;
+use thiserror::Error;
+
+use crate::notes::{NoteEntry, normalize_tag};
+use crate::time::{Clock, TimePoint, Timezone, parse_time_point};
+
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("unrecognized date or time: {0:?}")]
+    InvalidTime(String),
+}
+
+/// Date-range and tag options exactly as given on the command line.
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    #[arg(
+        long = "since",
+        value_name = "WHEN",
+        help = "only notes at or after WHEN"
+    )]
+    pub since: Option<String>,
+    #[arg(
+        long = "until",
+        value_name = "WHEN",
+        help = "only notes at or before WHEN (a date includes the whole day)"
+    )]
+    pub until: Option<String>,
+    #[arg(long = "on", value_name = "DATE", conflicts_with_all = ["since", "until"], help = "only notes on DATE")]
+    pub on: Option<String>,
+    #[arg(
+        long = "tag",
+        value_name = "TAG",
+        help = "only notes tagged #TAG (repeat to require several)"
+    )]
+    pub tags: Vec<String>,
+}
+
+impl FilterArgs {
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none() && self.until.is_none() && self.on.is_none() && self.tags.is_empty()
+    }
+}
+
+/// Selects notes by timestamp and tags. Unparsed entries only pass an unbounded filter.
+#[derive(Debug, Clone)]
+pub struct NoteFilter {
+    start: Bound<DateTime<FixedOffset>>,
+    end: Bound<DateTime<FixedOffset>>,
+    tags: Vec<String>,
+}
+
+impl Default for NoteFilter {
+    fn default() -> Self {
+        Self {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl NoteFilter {
+    /// Builds a filter from `--since`/`--until`/`--on`/`--tag`, resolving relative
+    /// expressions against `clock`. A whole-day `--until` includes that day.
+    pub fn from_filter_args<C: Clock + ?Sized>(
+        args: &FilterArgs,
+        pattern: &str,
+        clock: &C,
+    ) -> Result<Self, QueryError> {
+        let parse = |s: &str| {
+            parse_time_point(s, pattern, clock).ok_or_else(|| QueryError::InvalidTime(s.into()))
+        };
+        let timezone = clock.timezone();
+        let mut filter = NoteFilter::default();
+        if let Some(on) = &args.on {
+            let day = TimePoint::Day(parse(on)?.date());
+            filter.start = start_bound(&day, on, timezone)?;
+            filter.end = end_bound(&day, on, timezone)?;
+        }
+        if let Some(since) = &args.since {
+            filter.start = start_bound(&parse(since)?, since, timezone)?;
+        }
+        if let Some(until) = &args.until {
+            filter.end = end_bound(&parse(until)?, until, timezone)?;
+        }
+        filter.tags = args.tags.iter().map(|t| normalize_tag(t)).collect();
+        Ok(filter)
+    }
+
+    /// Earliest timestamp that passes.
+    pub fn start(&self) -> Bound<DateTime<FixedOffset>> {
+        self.start
+    }
+
+    /// Latest timestamp that passes.
+    pub fn end(&self) -> Bound<DateTime<FixedOffset>> {
+        self.end
+    }
+
+    /// Normalized tags a note must all carry.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        matches!((self.start, self.end), (Bound::Unbounded, Bound::Unbounded))
+            && self.tags.is_empty()
+    }
+
+    pub fn matches(&self, entry: &NoteEntry) -> bool {
+        match entry.as_note() {
+            Some(note) => {
+                (self.start, self.end).contains(&note.timestamp)
+                    && self.tags.iter().all(|t| note.tags.contains(t))
+            }
+            None => self.is_unbounded(),
+        }
+    }
+}
+
+fn start_bound(
+    point: &TimePoint,
+    input: &str,
+    timezone: Timezone,
+) -> Result<Bound<DateTime<FixedOffset>>, QueryError> {
+    point
+        .start(timezone)
+        .map(Bound::Included)
+        .ok_or_else(|| QueryError::InvalidTime(input.into()))
+}
+
+fn end_bound(
+    point: &TimePoint,
+    input: &str,
+    timezone: Timezone,
+) -> Result<Bound<DateTime<FixedOffset>>, QueryError> {
+    match point {
+        TimePoint::Day(date) => date
+            .checked_add_days(Days::new(1))
+            .and_then(|next| TimePoint::Day(next).start(timezone))
+            .map(Bound::Excluded),
+        TimePoint::Instant(dt) => Some(Bound::Included(*dt)),
+    }
+    .ok_or_else(|| QueryError::InvalidTime(input.into()))
+}
@@ -0,0 +1,100 @@
+use clap::Args;
+use regex::{Regex, RegexBuilder};
+
+use crate::notes::NoteEntry;
+
+/// Match-mode options shared by `nt search` and `nt -s`.
+#[derive(Args, Debug, Clone, Default)]
+pub struct SearchModeArgs {
+    #[arg(
+        short = 'w',
+        long = "word",
+        conflicts_with = "regex",
+        help = "match whole words only"
+    )]
+    pub word: bool,
+    #[arg(
+        short = 'r',
+        long = "regex",
+        help = "treat PATTERN as a regular expression"
+    )]
+    pub regex: bool,
+    #[arg(long = "count", help = "print only the number of matching notes")]
+    pub count: bool,
+}
+
+impl SearchModeArgs {
+    pub fn is_empty(&self) -> bool {
+        !self.word && !self.regex && !self.count
+    }
+
+    pub fn mode(&self) -> SearchMode {
+        if self.regex {
+            SearchMode::Regex
+        } else if self.word {
+            SearchMode::WholeWord
+        } else {
+            SearchMode::Substring
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Case-insensitive substring.
+    Substring,
+    /// Case-insensitive whole word.
+    WholeWord,
+    /// Regular expression, case-sensitive unless the pattern says otherwise.
+    Regex,
+}
+
+/// `pattern` as a regex that only matches at word boundaries. `\b` is only added next to
+/// word characters, since it would never match beside the `#` of `#infra` or the `++`
+/// of `c++`.
+fn whole_word_regex(pattern: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let boundary = |c: Option<char>| if c.is_some_and(is_word) { r"\b" } else { "" };
+    format!(
+        "{}{}{}",
+        boundary(pattern.chars().next()),
+        regex::escape(pattern),
+        boundary(pattern.chars().next_back())
+    )
+}
+
+/// Matches note text; unparsed lines are matched on their raw contents.
+#[derive(Debug, Clone)]
+pub struct NoteMatcher {
+    regex: Regex,
+    literal: Option<String>,
+}
+
+impl NoteMatcher {
+    pub fn new(pattern: &str, mode: SearchMode) -> Result<Self, regex::Error> {
+        let regex = match mode {
+            SearchMode::Substring => RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(true)
+                .build()?,
+            SearchMode::WholeWord => RegexBuilder::new(&whole_word_regex(pattern))
+                .case_insensitive(true)
+                .build()?,
+            SearchMode::Regex => Regex::new(pattern)?,
+        };
+        let literal = (mode != SearchMode::Regex).then(|| pattern.to_string());
+        Ok(Self { regex, literal })
+    }
+
+    /// Text that every match contains (ignoring case), for stores that index substrings.
+    /// None for regular expressions.
+    pub fn literal(&self) -> Option<&str> {
+        self.literal.as_deref()
+    }
+
+    pub fn is_match(&self, entry: &NoteEntry) -> bool {
+        match entry {
+            NoteEntry::Parsed(note) => self.regex.is_match(&note.text),
+            NoteEntry::Unparsed(raw) => self.regex.is_match(raw),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::notes::{NOTE_ID_LEN, NoteEntry};
+use crate::search::{NoteMatcher, SearchMode};
+
+/// A reference to one note in the file.
+///
+/// - `@N`: the N-th note counting back from the most recent (`@1` is the last note)
+/// - `#ID`: the note whose ID starts with `ID` (at least 4 hex digits)
+/// - `/REGEX/`: the most recent note whose text matches `REGEX`
+#[derive(Debug, Clone)]
+pub enum NoteSelector {
+    FromEnd(usize),
+    Id(String),
+    LastMatch {
+        pattern: String,
+        matcher: NoteMatcher,
+    },
+}
+
+const MIN_ID_PREFIX_LEN: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SelectorError {
+    #[error("invalid note selector {0:?} (expected @N, #ID or /REGEX/)")]
+    Invalid(String),
+    #[error("no note matches {0}")]
+    NotFound(String),
+    #[error("{selector} is ambiguous; it matches {count} notes")]
+    Ambiguous { selector: String, count: usize },
+}
+
+impl NoteSelector {
+    pub const LAST: NoteSelector = NoteSelector::FromEnd(1);
+
+    /// Index into `entries` of the selected note.
+    pub fn resolve(&self, entries: &[NoteEntry]) -> Result<usize, SelectorError> {
+        let not_found = || SelectorError::NotFound(self.to_string());
+        match self {
+            NoteSelector::FromEnd(n) => entries.len().checked_sub(*n).ok_or_else(not_found),
+            NoteSelector::Id(prefix) => {
+                let mut matching =
+                    (0..entries.len()).filter(|&i| entries[i].id().starts_with(prefix));
+                let first = matching.next().ok_or_else(not_found)?;
+                match matching.count() {
+                    0 => Ok(first),
+                    more => Err(SelectorError::Ambiguous {
+                        selector: self.to_string(),
+                        count: more + 1,
+                    }),
+                }
+            }
+            NoteSelector::LastMatch { matcher, .. } => (0..entries.len())
+                .rev()
+                .find(|&i| matcher.is_match(&entries[i]))
+                .ok_or_else(not_found),
+        }
+    }
+}
+
+impl FromStr for NoteSelector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SelectorError::Invalid(s.to_string());
+        if let Some(n) = s.strip_prefix('@') {
+            return match n.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(NoteSelector::FromEnd(n)),
+                _ => Err(invalid()),
+            };
+        }
+        if let Some(id) = s.strip_prefix('#') {
+            let id = id.to_ascii_lowercase();
+            if (MIN_ID_PREFIX_LEN..=NOTE_ID_LEN).contains(&id.len())
+                && id.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Ok(NoteSelector::Id(id));
+            }
+            return Err(invalid());
+        }
+        if let Some(pattern) = s.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+            let matcher = NoteMatcher::new(pattern, SearchMode::Regex).map_err(|_| invalid())?;
+            return Ok(NoteSelector::LastMatch {
+                pattern: pattern.to_string(),
+                matcher,
+            });
+        }
+        Err(invalid())
+    }
+}
+
+impl fmt::Display for NoteSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoteSelector::FromEnd(n) => write!(f, "@{n}"),
+            NoteSelector::Id(id) => write!(f, "#{id}"),
+            NoteSelector::LastMatch { pattern, .. } => write!(f, "/{pattern}/"),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! A [`NoteStore`] kept in a local SQLite database (the `sqlite` feature).
+//!
+//! Notes live in a `notes` table in insertion order, with their tags in `note_tags` and
+//! their text in an FTS5 trigram index, so time ranges, tag filters and substring
+//! searches only look at candidate rows. Records are exchanged as JSON Lines objects,
+//! like [`StorageFormat::Jsonl`](crate::notes::StorageFormat::Jsonl).
+
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+use std::io;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat};
+use rusqlite::types::Value;
+use rusqlite::{Connection, params, params_from_iter};
+
+use crate::lock::LOCK_TIMEOUT;
+use crate::notes::{Durability, Note, NoteEntry, NoteParser};
+use crate::query::NoteFilter;
+use crate::search::NoteMatcher;
+use crate::store::{LockGuard, NoteStore, Records, StoreLock};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS notes (
+        seq INTEGER PRIMARY KEY,
+        ts TEXT,
+        unix_ts INTEGER,
+        text TEXT NOT NULL,
+        id TEXT
+    );
+    CREATE INDEX IF NOT EXISTS notes_unix_ts ON notes (unix_ts);
+    CREATE TABLE IF NOT EXISTS note_tags (
+        seq INTEGER NOT NULL REFERENCES notes (seq) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (tag, seq)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS note_tags_seq ON note_tags (seq);
+    CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5 (
+        text, content = 'notes', content_rowid = 'seq', tokenize = 'trigram'
+    );
+    CREATE TRIGGER IF NOT EXISTS notes_fts_insert AFTER INSERT ON notes BEGIN
+        INSERT INTO notes_fts (rowid, text) VALUES (new.seq, new.text);
+    END;
+    CREATE TRIGGER IF NOT EXISTS notes_fts_delete AFTER DELETE ON notes BEGIN
+        INSERT INTO notes_fts (notes_fts, rowid, text) VALUES ('delete', old.seq, old.text);
+    END;
+    CREATE TRIGGER IF NOT EXISTS notes_fts_update AFTER UPDATE ON notes BEGIN
+        INSERT INTO notes_fts (notes_fts, rowid, text) VALUES ('delete', old.seq, old.text);
+        INSERT INTO notes_fts (rowid, text) VALUES (new.seq, new.text);
+    END;
+";
+
+/// Rows fetched per query while iterating, so taking a few records stays cheap.
+const PAGE_SIZE: usize = 256;
+
+/// The trigram index only finds substrings of at least this many characters.
+const MIN_FTS_LITERAL_LEN: usize = 3;
+
+/// Notes in a SQLite database file, created on first write.
+#[derive(Debug)]
+pub struct SqliteStore {
+    path: PathBuf,
+    parser: NoteParser,
+    durability: Durability,
+    connection: OnceCell<Connection>,
+}
+
+impl SqliteStore {
+    pub fn new(path: PathBuf, parser: NoteParser, durability: Durability) -> Self {
+        Self {
+            path,
+            parser,
+            durability,
+            connection: OnceCell::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The open database, creating the file and schema if needed.
+    fn connection(&self) -> io::Result<&Connection> {
+        if let Some(conn) = self.connection.get() {
+            return Ok(conn);
+        }
+        let conn = Connection::open(&self.path).map_err(io::Error::other)?;
+        conn.busy_timeout(LOCK_TIMEOUT).map_err(io::Error::other)?;
+        let synchronous = match self.durability {
+            Durability::None => "OFF",
+            Durability::Flush => "NORMAL",
+            Durability::Fsync => "FULL",
+        };
+        conn.execute_batch(&format!(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = {synchronous};
+             PRAGMA foreign_keys = ON;
+             {SCHEMA}"
+        ))
+        .map_err(io::Error::other)?;
+        Ok(self.connection.get_or_init(|| conn))
+    }
+
+    /// The open database; None when nothing has ever been stored.
+    fn existing_connection(&self) -> io::Result<Option<&Connection>> {
+        if self.connection.get().is_none() && !self.path.exists() {
+            return Ok(None);
+        }
+        self.connection().map(Some)
+    }
+
+    /// Runs `write` atomically: in its own transaction, or in a savepoint when
+    /// [`NoteStore::lock`] already holds one.
+    fn write<T>(&self, write: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> io::Result<T> {
+        let conn = self.connection()?;
+        let (begin, commit, rollback) = if conn.is_autocommit() {
+            ("BEGIN IMMEDIATE", "COMMIT", "ROLLBACK")
+        } else {
+            (
+                "SAVEPOINT nt_write",
+                "RELEASE nt_write",
+                "ROLLBACK TO nt_write; RELEASE nt_write",
+            )
+        };
+        conn.execute_batch(begin).map_err(io::Error::other)?;
+        match write(conn) {
+            Ok(value) => {
+                conn.execute_batch(commit).map_err(io::Error::other)?;
+                Ok(value)
+            }
+            Err(e) => {
+                let _ = conn.execute_batch(rollback);
+                Err(io::Error::other(e))
+            }
+        }
+    }
+
+    fn insert(&self, conn: &Connection, record: &str) -> rusqlite::Result<()> {
+        match self.parser.parse_line(record) {
+            NoteEntry::Parsed(note) => {
+                conn.prepare_cached(
+                    "INSERT INTO notes (ts, unix_ts, text, id) VALUES (?1, ?2, ?3, ?4)",
+                )?
+                .execute(params![
+                    note.timestamp.to_rfc3339_opts(SecondsFormat::Secs, false),
+                    note.timestamp.timestamp(),
+                    note.text,
+                    note.id,
+                ])?;
+                let seq = conn.last_insert_rowid();
+                let mut insert_tag =
+                    conn.prepare_cached("INSERT INTO note_tags (seq, tag) VALUES (?1, ?2)")?;
+                for tag in &note.tags {
+                    insert_tag.execute(params![seq, tag])?;
+                }
+            }
+            NoteEntry::Unparsed(raw) => {
+                conn.prepare_cached("INSERT INTO notes (text) VALUES (?1)")?
+                    .execute(params![raw])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows matching `query`, paged in `seq` order; None when nothing has been stored.
+    fn rows(&self, query: RowQuery) -> io::Result<Option<Rows<'_>>> {
+        Ok(self
+            .existing_connection()?
+            .map(|conn| Rows::new(conn, query)))
+    }
+
+    fn records_in(&self, reverse: bool) -> io::Result<Option<Records<'_>>> {
+        let parser = &self.parser;
+        let query = RowQuery {
+            reverse,
+            ..RowQuery::default()
+        };
+        Ok(self.rows(query)?.map(|rows| {
+            Box::new(rows.map(move |row| row.map(|row| row.record(parser)))) as Records<'_>
+        }))
+    }
+}
+
+impl NoteStore for SqliteStore {
+    fn parser(&self) -> &NoteParser {
+        &self.parser
+    }
+
+    fn append(&self, record: &str) -> io::Result<()> {
+        self.write(|conn| self.insert(conn, record))
+    }
+
+    fn records(&self) -> io::Result<Option<Records<'_>>> {
+        self.records_in(false)
+    }
+
+    fn records_rev(&self) -> io::Result<Option<Records<'_>>> {
+        self.records_in(true)
+    }
+
+    fn rewrite(&self, records: &[String]) -> io::Result<()> {
+        self.write(|conn| {
+            conn.execute("DELETE FROM notes", [])?;
+            records.iter().try_for_each(|r| self.insert(conn, r))
+        })
+    }
+
+    fn lock(&self) -> io::Result<StoreLock<'_>> {
+        let conn = self.connection()?;
+        conn.execute_batch("BEGIN IMMEDIATE")
+            .map_err(io::Error::other)?;
+        Ok(StoreLock::with_guard(Transaction(conn)))
+    }
+
+    fn find_entries(
+        &self,
+        filter: &NoteFilter,
+        matcher: Option<&NoteMatcher>,
+    ) -> io::Result<Option<Vec<NoteEntry>>> {
+        let Some(rows) = self.rows(RowQuery::prefilter(filter, matcher))? else {
+            return Ok(None);
+        };
+        let mut selected = Vec::new();
+        for row in rows {
+            let entry = row?.entry();
+            if filter.matches(&entry) && matcher.is_none_or(|m| m.is_match(&entry)) {
+                selected.push(entry);
+            }
+        }
+        Ok(Some(selected))
+    }
+
+    fn last_entries(
+        &self,
+        count: usize,
+        filter: &NoteFilter,
+    ) -> io::Result<Option<Vec<NoteEntry>>> {
+        let query = RowQuery {
+            reverse: true,
+            ..RowQuery::prefilter(filter, None)
+        };
+        let Some(rows) = self.rows(query)? else {
+            return Ok(None);
+        };
+        let mut selected = Vec::new();
+        for row in rows {
+            if selected.len() == count {
+                break;
+            }
+            let entry = row?.entry();
+            if filter.matches(&entry) {
+                selected.push(entry);
+            }
+        }
+        selected.reverse();
+        Ok(Some(selected))
+    }
+}
+
+/// The transaction behind a [`StoreLock`]; committed when the lock is released, and
+/// rolled back when it is dropped without being released.
+struct Transaction<'a>(&'a Connection);
+
+impl LockGuard for Transaction<'_> {
+    fn release(self: Box<Self>) -> io::Result<()> {
+        self.0.execute_batch("COMMIT").map_err(io::Error::other)
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.0.is_autocommit() {
+            let _ = self.0.execute_batch("ROLLBACK");
+        }
+    }
+}
+
+/// Conditions that narrow the rows read to candidates; callers still apply the exact
+/// filter and matcher to what comes back.
+#[derive(Default)]
+struct RowQuery {
+    conditions: Vec<String>,
+    params: Vec<Value>,
+    reverse: bool,
+}
+
+impl RowQuery {
+    fn prefilter(filter: &NoteFilter, matcher: Option<&NoteMatcher>) -> Self {
+        let mut query = Self::default();
+        if !filter.is_unbounded() {
+            query.conditions.push("unix_ts IS NOT NULL".into());
+        }
+        // Stored timestamps are whole seconds, so comparing seconds only widens the range.
+        if let Bound::Included(start) | Bound::Excluded(start) = filter.start() {
+            query.push("unix_ts >= ?", Value::Integer(start.timestamp()));
+        }
+        if let Bound::Included(end) | Bound::Excluded(end) = filter.end() {
+            query.push("unix_ts <= ?", Value::Integer(end.timestamp()));
+        }
+        for tag in filter.tags() {
+            query.push(
+                "seq IN (SELECT seq FROM note_tags WHERE tag = ?)",
+                Value::Text(tag.clone()),
+            );
+        }
+        // The trigram tokenizer folds ASCII case the same way the matcher does; other
+        // scripts fall back to scanning every row.
+        if let Some(literal) = matcher.and_then(NoteMatcher::literal)
+            && literal.is_ascii()
+            && literal.len() >= MIN_FTS_LITERAL_LEN
+        {
+            query.push(
+                "seq IN (SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?)",
+                Value::Text(format!("\"{}\"", literal.replace('"', "\"\""))),
+            );
+        }
+        query
+    }
+
+    fn push(&mut self, condition: &str, param: Value) {
+        self.conditions
+            .push(condition.replace('?', &format!("?{}", self.params.len() + 1)));
+        self.params.push(param);
+    }
+
+    fn sql(&self) -> String {
+        let cursor = format!(
+            "seq {} ?{}",
+            if self.reverse { "<" } else { ">" },
+            self.params.len() + 1
+        );
+        let conditions: Vec<&str> = self
+            .conditions
+            .iter()
+            .map(String::as_str)
+            .chain([cursor.as_str()])
+            .collect();
+        format!(
+            "SELECT seq, ts, text FROM notes WHERE {} ORDER BY seq {} LIMIT {PAGE_SIZE}",
+            conditions.join(" AND "),
+            if self.reverse { "DESC" } else { "ASC" },
+        )
+    }
+}
+
+/// One row of `notes`.
+struct Row {
+    seq: i64,
+    ts: Option<String>,
+    text: String,
+}
+
+impl Row {
+    fn entry(self) -> NoteEntry {
+        match self.ts.as_deref().map(DateTime::parse_from_rfc3339) {
+            Some(Ok(timestamp)) => NoteEntry::Parsed(Note::new(timestamp, self.text)),
+            _ => NoteEntry::Unparsed(self.text),
+        }
+    }
+
+    fn record(self, parser: &NoteParser) -> String {
+        match self.entry() {
+            NoteEntry::Parsed(note) => parser.format_note(&note),
+            NoteEntry::Unparsed(raw) => raw,
+        }
+    }
+}
+
+/// Iterates the rows of a [`RowQuery`] a page at a time.
+struct Rows<'a> {
+    conn: &'a Connection,
+    sql: String,
+    params: Vec<Value>,
+    cursor: i64,
+    page: VecDeque<Row>,
+    done: bool,
+}
+
+impl<'a> Rows<'a> {
+    fn new(conn: &'a Connection, query: RowQuery) -> Self {
+        Self {
+            conn,
+            sql: query.sql(),
+            cursor: if query.reverse { i64::MAX } else { 0 },
+            params: query.params,
+            page: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    fn fetch_page(&mut self) -> rusqlite::Result<()> {
+        let mut statement = self.conn.prepare_cached(&self.sql)?;
+        let cursor = Value::Integer(self.cursor);
+        let params = self.params.iter().chain([&cursor]);
+        let rows = statement.query_map(params_from_iter(params), |row| {
+            Ok(Row {
+                seq: row.get(0)?,
+                ts: row.get(1)?,
+                text: row.get(2)?,
+            })
+        })?;
+        self.page = rows.collect::<rusqlite::Result<_>>()?;
+        self.done = self.page.len() < PAGE_SIZE;
+        if let Some(last) = self.page.back() {
+            self.cursor = last.seq;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Rows<'_> {
+    type Item = io::Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.page.is_empty()
+            && !self.done
+            && let Err(e) = self.fetch_page()
+        {
+            self.done = true;
+            return Some(Err(io::Error::other(e)));
+        }
+        self.page.pop_front().map(Ok)
+    }
+}
@@ -0,0 +1,295 @@
+use std::fs::OpenOptions;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::lock::{LOCK_TIMEOUT, NoteLock};
+use crate::notes::{
+    Durability, NoteEntry, NoteParser, StorageFormat, TAIL_BLOCK_SIZE, append_record_to_file,
+    logical_lines, logical_lines_rev, rewrite_records_to_file,
+};
+use crate::query::NoteFilter;
+use crate::search::NoteMatcher;
+use crate::time::Clock;
+
+/// Records in the order a [`NoteStore`] yields them.
+pub type Records<'a> = Box<dyn Iterator<Item = io::Result<String>> + 'a>;
+
+/// Where notes are kept.
+///
+/// A store holds records: serialized notes that its [`NoteParser`] turns into
+/// [`NoteEntry`] values and builds for new notes. Commands only go through this trait,
+/// so a library user can plug in their own storage.
+pub trait NoteStore {
+    /// Parses this store's records and builds new ones.
+    fn parser(&self) -> &NoteParser;
+
+    /// Appends one record without interleaving with concurrent writers.
+    fn append(&self, record: &str) -> io::Result<()>;
+
+    /// Every record, oldest first; None when nothing has ever been stored.
+    fn records(&self) -> io::Result<Option<Records<'_>>>;
+
+    /// Every record, newest first; None when nothing has ever been stored. Stores should
+    /// only do work for the records actually taken from the iterator.
+    fn records_rev(&self) -> io::Result<Option<Records<'_>>>;
+
+    /// Replaces all records. Callers must hold [`NoteStore::lock`] from before they read
+    /// the records they are replacing, and the store must never expose a partial rewrite.
+    fn rewrite(&self, records: &[String]) -> io::Result<()>;
+
+    /// Takes the store's write lock; [`NoteStore::append`] waits for it too.
+    fn lock(&self) -> io::Result<StoreLock<'_>>;
+
+    /// Appends a note with the given text, timestamped by `clock`.
+    fn append_note(&self, clock: &dyn Clock, text: &str) -> io::Result<()> {
+        self.append(&self.parser().new_record(clock, text))
+    }
+
+    /// Reads every record, oldest first.
+    fn read_records(&self) -> io::Result<Option<Vec<String>>> {
+        self.records()?.map(Iterator::collect).transpose()
+    }
+
+    /// Reads and parses every record, oldest first.
+    fn entries(&self) -> io::Result<Option<Vec<NoteEntry>>> {
+        let parser = self.parser();
+        self.records()?
+            .map(|records| records.map(|r| r.map(|r| parser.parse_line(&r))).collect())
+            .transpose()
+    }
+
+    /// Entries that pass `filter` and, when given, `matcher`, oldest first. Stores with
+    /// indexes override this to avoid reading every record.
+    fn find_entries(
+        &self,
+        filter: &NoteFilter,
+        matcher: Option<&NoteMatcher>,
+    ) -> io::Result<Option<Vec<NoteEntry>>> {
+        Ok(self.entries()?.map(|entries| {
+            entries
+                .into_iter()
+                .filter(|e| filter.matches(e) && matcher.is_none_or(|m| m.is_match(e)))
+                .collect()
+        }))
+    }
+
+    /// The newest `count` entries that pass `filter`, oldest first. Reads from the end of
+    /// the store and stops once enough entries are found.
+    fn last_entries(
+        &self,
+        count: usize,
+        filter: &NoteFilter,
+    ) -> io::Result<Option<Vec<NoteEntry>>> {
+        let parser = self.parser();
+        let Some(records) = self.records_rev()? else {
+            return Ok(None);
+        };
+        let mut selected = Vec::new();
+        for record in records {
+            if selected.len() == count {
+                break;
+            }
+            let entry = parser.parse_line(&record?);
+            if filter.matches(&entry) {
+                selected.push(entry);
+            }
+        }
+        selected.reverse();
+        Ok(Some(selected))
+    }
+}
+
+/// Opens the store for `path` in the format of `parser`.
+pub fn open_store(
+    path: PathBuf,
+    parser: NoteParser,
+    durability: Durability,
+) -> io::Result<Box<dyn NoteStore>> {
+    match parser.format() {
+        StorageFormat::Text | StorageFormat::Jsonl => {
+            Ok(Box::new(FileStore::new(path, parser, durability)))
+        }
+        #[cfg(feature = "sqlite")]
+        StorageFormat::Sqlite => Ok(Box::new(crate::sqlite::SqliteStore::new(
+            path, parser, durability,
+        ))),
+        #[cfg(not(feature = "sqlite"))]
+        StorageFormat::Sqlite => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "format = \"sqlite\" needs nt built with the `sqlite` feature",
+        )),
+    }
+}
+
+/// What keeps a [`NoteStore`] locked.
+pub trait LockGuard {
+    /// Unlocks the store, making the writes made under the lock final.
+    fn release(self: Box<Self>) -> io::Result<()>;
+}
+
+/// A guard that unlocks when it is dropped and has nothing left to write.
+struct DropGuard<G>(G);
+
+impl<G> LockGuard for DropGuard<G> {
+    fn release(self: Box<Self>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A held [`NoteStore`] write lock. Callers end it with [`StoreLock::release`], which
+/// reports whether the writes made under it were kept; dropping it instead also unlocks
+/// the store, but writes that are not yet final (as in the SQLite store) are discarded.
+pub struct StoreLock<'a> {
+    guard: Box<dyn LockGuard + 'a>,
+}
+
+impl<'a> StoreLock<'a> {
+    /// Wraps whatever keeps a store locked until it is dropped.
+    pub fn new<G: 'a>(guard: G) -> Self {
+        Self::with_guard(DropGuard(guard))
+    }
+
+    /// Wraps a guard that has work to do when the lock is released.
+    pub fn with_guard<G: LockGuard + 'a>(guard: G) -> Self {
+        Self {
+            guard: Box::new(guard),
+        }
+    }
+
+    pub fn release(self) -> io::Result<()> {
+        self.guard.release()
+    }
+}
+
+/// The note file on disk, in the text or JSON Lines [`StorageFormat`](crate::notes::StorageFormat).
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+    parser: NoteParser,
+    durability: Durability,
+}
+
+impl FileStore {
+    pub fn new(path: PathBuf, parser: NoteParser, durability: Durability) -> Self {
+        Self {
+            path,
+            parser,
+            durability,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens the note file for reading; None when it does not exist yet.
+    fn open(&self) -> io::Result<Option<std::fs::File>> {
+        match OpenOptions::new().read(true).open(&self.path) {
+            Ok(file) => Ok(Some(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl NoteStore for FileStore {
+    fn parser(&self) -> &NoteParser {
+        &self.parser
+    }
+
+    fn append(&self, record: &str) -> io::Result<()> {
+        append_record_to_file(&self.path, record, self.durability)
+    }
+
+    fn records(&self) -> io::Result<Option<Records<'_>>> {
+        Ok(self
+            .open()?
+            .map(|file| Box::new(logical_lines(BufReader::new(file))) as Records<'_>))
+    }
+
+    fn records_rev(&self) -> io::Result<Option<Records<'_>>> {
+        match self.open()? {
+            Some(file) => Ok(Some(Box::new(logical_lines_rev(file, TAIL_BLOCK_SIZE)?))),
+            None => Ok(None),
+        }
+    }
+
+    fn rewrite(&self, records: &[String]) -> io::Result<()> {
+        rewrite_records_to_file(&self.path, records)
+    }
+
+    fn lock(&self) -> io::Result<StoreLock<'_>> {
+        NoteLock::acquire(&self.path, LOCK_TIMEOUT).map(StoreLock::new)
+    }
+}
+
+/// A store that keeps its records in memory, for tests and embedding.
+#[derive(Debug)]
+pub struct MemoryStore {
+    parser: NoteParser,
+    records: Mutex<Option<Vec<String>>>,
+    write_lock: Mutex<()>,
+}
+
+impl MemoryStore {
+    /// An empty store; like a missing note file, it has no records until the first append.
+    pub fn new(parser: NoteParser) -> Self {
+        Self {
+            parser,
+            records: Mutex::new(None),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn with_records(parser: NoteParser, records: Vec<String>) -> Self {
+        Self {
+            parser,
+            records: Mutex::new(Some(records)),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn snapshot(&self) -> Option<Vec<String>> {
+        self.records.lock().expect("memory store poisoned").clone()
+    }
+}
+
+impl NoteStore for MemoryStore {
+    fn parser(&self) -> &NoteParser {
+        &self.parser
+    }
+
+    fn append(&self, record: &str) -> io::Result<()> {
+        let lock = self.lock()?;
+        self.records
+            .lock()
+            .expect("memory store poisoned")
+            .get_or_insert_with(Vec::new)
+            .push(record.to_string());
+        lock.release()
+    }
+
+    fn records(&self) -> io::Result<Option<Records<'_>>> {
+        Ok(self
+            .snapshot()
+            .map(|records| Box::new(records.into_iter().map(Ok)) as Records<'_>))
+    }
+
+    fn records_rev(&self) -> io::Result<Option<Records<'_>>> {
+        Ok(self
+            .snapshot()
+            .map(|records| Box::new(records.into_iter().rev().map(Ok)) as Records<'_>))
+    }
+
+    fn rewrite(&self, records: &[String]) -> io::Result<()> {
+        *self.records.lock().expect("memory store poisoned") = Some(records.to_vec());
+        Ok(())
+    }
+
+    fn lock(&self) -> io::Result<StoreLock<'_>> {
+        Ok(StoreLock::new(
+            self.write_lock.lock().expect("memory store poisoned"),
+        ))
+    }
+}
@@ -1,25 +1,342 @@
-use chrono::Local;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use chrono::format::{Item, Parsed, StrftimeItems, parse_and_remainder};
+use chrono::{
+    DateTime, Datelike, Days, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use thiserror::Error;
 
 pub trait Clock {
-    fn now_formatted(&self, pattern: &str) -> String;
+    fn now(&self) -> DateTime<FixedOffset>;
+
+    /// The zone that day names and times of day such as `today 14:00` are read in.
+    fn timezone(&self) -> Timezone {
+        Timezone::Local
+    }
+}
+
+/// The zone notes are stamped in and offset-less timestamps are read in: the
+/// machine's local zone, UTC, or an IANA zone such as `Europe/Berlin`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Timezone {
+    #[default]
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unknown timezone {0:?} (expected UTC, local or an IANA name such as Europe/Berlin)")]
+pub struct UnknownTimezone(pub String);
+
+impl FromStr for Timezone {
+    type Err = UnknownTimezone;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            Ok(Timezone::Local)
+        } else if s.eq_ignore_ascii_case("utc") {
+            Ok(Timezone::Utc)
+        } else {
+            s.parse::<Tz>()
+                .map(Timezone::Named)
+                .map_err(|_| UnknownTimezone(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Local => f.write_str("local"),
+            Timezone::Utc => f.write_str("UTC"),
+            Timezone::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+/// Picks the earlier instant when a DST transition makes `naive` ambiguous.
+fn resolve_in<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => Some(dt.fixed_offset()),
+        LocalResult::None => None,
+    }
 }
 
-pub struct SystemClock;
+impl Timezone {
+    /// Interprets a wall-clock time in this zone, picking the earlier instant when a
+    /// DST transition makes it ambiguous.
+    pub fn resolve(self, naive: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Timezone::Local => resolve_in(&Local, naive),
+            Timezone::Utc => resolve_in(&Utc, naive),
+            Timezone::Named(tz) => resolve_in(&tz, naive),
+        }
+    }
 
-impl Clock for SystemClock {
-    fn now_formatted(&self, pattern: &str) -> String {
-        Local::now().format(pattern).to_string()
+    /// The same instant as `dt`, with this zone's offset.
+    pub fn convert(self, dt: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Local => dt.with_timezone(&Local).fixed_offset(),
+            Timezone::Utc => dt.with_timezone(&Utc).fixed_offset(),
+            Timezone::Named(tz) => dt.with_timezone(&tz).fixed_offset(),
+        }
     }
 }
 
-#[cfg(test)]
-pub struct FixedClock {
-    pub formatted: String,
+/// The system clock, read in this zone.
+impl Clock for Timezone {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.convert(Utc::now().fixed_offset())
+    }
+
+    fn timezone(&self) -> Timezone {
+        *self
+    }
 }
 
-#[cfg(test)]
+/// A clock stopped at one instant; used for backdated notes and deterministic tests.
+pub struct FixedClock(pub DateTime<FixedOffset>);
+
 impl Clock for FixedClock {
-    fn now_formatted(&self, _pattern: &str) -> String {
-        self.formatted.clone()
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.0
+    }
+}
+
+/// A point in time as written by the user: either a whole calendar day or an exact instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePoint {
+    Day(NaiveDate),
+    Instant(DateTime<FixedOffset>),
+}
+
+impl TimePoint {
+    /// The first instant covered by this point, with whole days starting at midnight
+    /// in `timezone`.
+    pub fn start(&self, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+        match self {
+            TimePoint::Day(date) => timezone.resolve(date.and_time(NaiveTime::MIN)),
+            TimePoint::Instant(dt) => Some(*dt),
+        }
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            TimePoint::Day(date) => *date,
+            TimePoint::Instant(dt) => dt.date_naive(),
+        }
+    }
+}
+
+/// A specifier that timestamps cannot be written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InvalidSpecifier<'a> {
+    /// Not a specifier chrono knows, e.g. `%Q`, or a lone trailing `%`.
+    #[error("unknown specifier {0}")]
+    Unknown(&'a str),
+    /// Only accepted when parsing, e.g. `%#z`.
+    #[error("specifier {0} can only be parsed, not written")]
+    ParseOnly(&'a str),
+}
+
+/// The first specifier in `pattern` that chrono cannot format. Formatting with such a
+/// pattern panics.
+pub fn invalid_strftime_specifier(pattern: &str) -> Option<InvalidSpecifier<'_>> {
+    let mut rest = pattern;
+    while let Some(start) = rest.find('%') {
+        let spec = &rest[start..];
+        // A specifier runs to its conversion character, past any flags and widths
+        // such as `%-d`, `%.3f` or `%:z`.
+        let end = spec[1..]
+            .find(|c: char| c.is_ascii_alphabetic() || c == '%')
+            .map_or(spec.len(), |i| i + 2);
+        let spec = &spec[..end];
+        if StrftimeItems::new(spec).any(|item| matches!(item, Item::Error)) {
+            return Some(InvalidSpecifier::Unknown(spec));
+        }
+        // Parse-only items such as `%#z` are opaque, so try writing them.
+        let sample = DateTime::<Utc>::UNIX_EPOCH.fixed_offset();
+        if write!(String::new(), "{}", sample.format(spec)).is_err() {
+            return Some(InvalidSpecifier::ParseOnly(spec));
+        }
+        rest = &rest[start + end..];
+    }
+    None
+}
+
+/// Whether timestamps written with `pattern` carry their UTC offset, e.g. with `%z`,
+/// so that they read back as the same instant in any timezone.
+pub fn pattern_records_offset(pattern: &str) -> bool {
+    let Some(offset) = FixedOffset::east_opt(5 * 3600 + 30 * 60) else {
+        return false;
+    };
+    let sample = DateTime::<Utc>::UNIX_EPOCH.with_timezone(&offset);
+    let mut written = String::new();
+    if write!(written, "{}", sample.format(pattern)).is_err() {
+        return false;
+    }
+    let mut parsed = Parsed::new();
+    parse_and_remainder(&mut parsed, &written, StrftimeItems::new(pattern)).is_ok()
+        && parsed.offset().is_some()
+}
+
+/// Parses a timestamp written with `pattern` from the start of `input`, returning the
+/// remaining unparsed text. Timestamps without an offset are read in `timezone`, and
+/// patterns without a time of day yield [`TimePoint::Day`].
+pub fn parse_time_point_prefix<'a>(
+    input: &'a str,
+    pattern: &str,
+    timezone: Timezone,
+) -> Option<(TimePoint, &'a str)> {
+    let mut parsed = Parsed::new();
+    let remainder = parse_and_remainder(&mut parsed, input, StrftimeItems::new(pattern)).ok()?;
+    let date = parsed.to_naive_date().ok()?;
+    let Ok(time) = parsed.to_naive_time() else {
+        return Some((TimePoint::Day(date), remainder));
+    };
+    let naive = date.and_time(time);
+    let instant = match parsed.offset() {
+        Some(seconds) => FixedOffset::east_opt(seconds)?
+            .from_local_datetime(&naive)
+            .single()?,
+        None => timezone.resolve(naive)?,
+    };
+    Some((TimePoint::Instant(instant), remainder))
+}
+
+const ISO_8601_DATETIME_PATTERNS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d",
+];
+
+/// Parses an absolute date or datetime, trying the configured note pattern first and
+/// then common ISO 8601 forms (with or without an offset); times without an offset are
+/// read in `timezone`.
+pub fn parse_absolute_time_point(
+    input: &str,
+    pattern: &str,
+    timezone: Timezone,
+) -> Option<TimePoint> {
+    let input = input.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Some(TimePoint::Instant(dt));
+    }
+    std::iter::once(pattern)
+        .chain(ISO_8601_DATETIME_PATTERNS.iter().copied())
+        .find_map(|p| match parse_time_point_prefix(input, p, timezone) {
+            Some((point, "")) => Some(point),
+            _ => None,
+        })
+}
+
+/// Parses an absolute date/time (see [`parse_absolute_time_point`]) or a relative
+/// expression resolved against `clock` (see [`parse_relative_time_point`]).
+pub fn parse_time_point<C: Clock + ?Sized>(
+    input: &str,
+    pattern: &str,
+    clock: &C,
+) -> Option<TimePoint> {
+    parse_absolute_time_point(input, pattern, clock.timezone())
+        .or_else(|| parse_relative_time_point(input, clock))
+}
+
+/// Parses relative expressions such as `now`, `yesterday`, `2h ago`, `3 days ago`,
+/// `last monday`, `friday` and `today 14:00`. Day expressions without a time of day
+/// yield [`TimePoint::Day`] so that they cover the whole day in range filters.
+pub fn parse_relative_time_point<C: Clock + ?Sized>(input: &str, clock: &C) -> Option<TimePoint> {
+    let now = clock.now();
+    let lowered = input.trim().to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    match words.as_slice() {
+        ["now"] => return Some(TimePoint::Instant(now)),
+        [.., "ago"] => {
+            let amount: String = words[..words.len() - 1].concat();
+            return parse_duration_amount(&amount)
+                .and_then(|d| now.checked_sub_signed(d))
+                .map(TimePoint::Instant);
+        }
+        _ => {}
+    }
+    let today = now.date_naive();
+    let (date, rest) = match words.as_slice() {
+        ["today", rest @ ..] => (today, rest),
+        ["yesterday", rest @ ..] => (today.checked_sub_days(Days::new(1))?, rest),
+        ["tomorrow", rest @ ..] => (today.checked_add_days(Days::new(1))?, rest),
+        ["last", day, rest @ ..] => {
+            let weekday: Weekday = day.parse().ok()?;
+            (previous_weekday(today, weekday, false)?, rest)
+        }
+        [day, rest @ ..] => {
+            let weekday: Weekday = day.parse().ok()?;
+            (previous_weekday(today, weekday, true)?, rest)
+        }
+        [] => return None,
+    };
+    match rest {
+        [] => Some(TimePoint::Day(date)),
+        [time] => {
+            let time = parse_time_of_day(time)?;
+            clock
+                .timezone()
+                .resolve(date.and_time(time))
+                .map(TimePoint::Instant)
+        }
+        _ => None,
+    }
+}
+
+/// The most recent `weekday` on or before `today` (strictly before unless `include_today`).
+fn previous_weekday(today: NaiveDate, weekday: Weekday, include_today: bool) -> Option<NaiveDate> {
+    let mut back =
+        (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    if back == 0 && !include_today {
+        back = 7;
+    }
+    today.checked_sub_days(Days::new(back.into()))
+}
+
+/// Parses `14:00`, `9am` or `9:30pm`.
+fn parse_time_of_day(input: &str) -> Option<NaiveTime> {
+    let (clock_part, pm) = if let Some(v) = input.strip_suffix("am") {
+        (v, Some(false))
+    } else if let Some(v) = input.strip_suffix("pm") {
+        (v, Some(true))
+    } else {
+        (input, None)
+    };
+    let (hour, minute) = match clock_part.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None if pm.is_some() => (clock_part.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Parses amounts such as `30m`, `2h`, `90minutes`, `3days` or `1week`.
+fn parse_duration_amount(input: &str) -> Option<Duration> {
+    let split = input.find(|c: char| !c.is_ascii_digit())?;
+    let (digits, unit) = input.split_at(split);
+    let n: i64 = digits.parse().ok()?;
+    match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(n),
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(n),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(n),
+        "d" | "day" | "days" => Duration::try_days(n),
+        "w" | "week" | "weeks" => Duration::try_weeks(n),
+        _ => None,
     }
 }
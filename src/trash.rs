@@ -0,0 +1,140 @@
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::lock::{LOCK_TIMEOUT, NoteLock};
+use crate::notes::{encode_continuation_lines, logical_lines, rewrite_records_to_file};
+use crate::store::NoteStore;
+
+/// A record removed from the note file, remembered with the index it occupied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedRecord {
+    pub index: usize,
+    pub record: String,
+}
+
+const HEADER_PREFIX: &str = "removed ";
+
+/// The trash file that sits next to a note file: `daybook.txt` → `daybook.txt.trash`.
+pub fn trash_file_path(note_file: &Path) -> PathBuf {
+    let mut name = note_file
+        .file_name()
+        .map(OsString::from)
+        .unwrap_or_default();
+    name.push(".trash");
+    note_file.with_file_name(name)
+}
+
+/// Removes `indices` from `records` and the store, saving them to `trash` as one batch.
+///
+/// The trash is written before the store is rewritten, so an interruption can leave a
+/// note in both places but never in neither. Callers must hold the store's lock from
+/// before they read `records`, and release it to make the removal final; the trash has
+/// a lock of its own, taken here, because [`restore_last_trashed`] still updates it
+/// after releasing the store's.
+pub fn move_records_to_trash<S: NoteStore + ?Sized>(
+    store: &S,
+    trash: &Path,
+    records: &mut Vec<String>,
+    indices: &[usize],
+) -> io::Result<Vec<TrashedRecord>> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices.dedup();
+    let removed: Vec<TrashedRecord> = indices
+        .iter()
+        .map(|&index| TrashedRecord {
+            index,
+            record: records[index].clone(),
+        })
+        .collect();
+
+    let _trash_lock = NoteLock::acquire(trash, LOCK_TIMEOUT)?;
+    let batch = read_trash(trash)?.last().map_or(1, |(b, _)| b + 1);
+    let file = OpenOptions::new().create(true).append(true).open(trash)?;
+    let mut writer = BufWriter::new(file);
+    for item in &removed {
+        let entry = format!("{HEADER_PREFIX}{batch} {}\n{}", item.index, item.record);
+        writer.write_all(encode_continuation_lines(&entry).as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    // The rewrite below is durable, so the trash must be too before it happens.
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_data()?;
+
+    for index in indices.iter().rev() {
+        records.remove(*index);
+    }
+    store.rewrite(records)?;
+    Ok(removed)
+}
+
+/// Puts the most recently trashed batch from `trash` back at its original positions in
+/// the store. Returns None when the trash is empty.
+pub fn restore_last_trashed<S: NoteStore + ?Sized>(
+    store: &S,
+    trash: &Path,
+) -> io::Result<Option<Vec<TrashedRecord>>> {
+    if !trash.exists() {
+        return Ok(None);
+    }
+    let lock = store.lock()?;
+    let _trash_lock = NoteLock::acquire(trash, LOCK_TIMEOUT)?;
+    let mut batches = read_trash(trash)?;
+    let Some(&(last_batch, _)) = batches.last() else {
+        return Ok(None);
+    };
+    let split = batches.partition_point(|(b, _)| *b < last_batch);
+    let restored: Vec<TrashedRecord> = batches.drain(split..).map(|(_, r)| r).collect();
+
+    let mut records = store.read_records()?.unwrap_or_default();
+    for item in &restored {
+        let at = item.index.min(records.len());
+        records.insert(at, item.record.clone());
+    }
+    store.rewrite(&records)?;
+    // Only forget the batch once the restored notes are safely in the store. The trash
+    // stays locked, so a removal that starts now waits before adding its own batch.
+    lock.release()?;
+
+    if batches.is_empty() {
+        fs::remove_file(trash)?;
+    } else {
+        let remaining: Vec<String> = batches
+            .iter()
+            .map(|(b, item)| format!("{HEADER_PREFIX}{b} {}\n{}", item.index, item.record))
+            .collect();
+        rewrite_records_to_file(trash, &remaining)?;
+    }
+    Ok(Some(restored))
+}
+
+/// Reads the trash as `(batch, record)` pairs in file order; a missing trash is empty.
+fn read_trash(trash: &Path) -> io::Result<Vec<(u64, TrashedRecord)>> {
+    let file = match OpenOptions::new().read(true).open(trash) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for entry in logical_lines(BufReader::new(file)) {
+        let entry = entry?;
+        let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed trash entry");
+        let (header, record) = entry.split_once('\n').unwrap_or((&entry, ""));
+        let (batch, index) = header
+            .strip_prefix(HEADER_PREFIX)
+            .and_then(|rest| rest.split_once(' '))
+            .ok_or_else(malformed)?;
+        entries.push((
+            batch.parse().map_err(|_| malformed())?,
+            TrashedRecord {
+                index: index.parse().map_err(|_| malformed())?,
+                record: record.to_string(),
+            },
+        ));
+    }
+    Ok(entries)
+}
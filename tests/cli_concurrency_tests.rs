@@ -0,0 +1,127 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use tempfile::TempDir;
+
+use nt::notes::{NoteParser, read_all_notes_from_file_allow_missing};
+
+const WRITERS: usize = 6;
+const NOTES_PER_WRITER: usize = 25;
+/// Longer than a `BufWriter` buffer, so a note takes more than one `write` call.
+const LONG_LINE: usize = 12 * 1024;
+
+fn note_body(writer: usize, n: usize) -> String {
+    if n.is_multiple_of(5) {
+        let fill = char::from(b'a' + writer as u8).to_string();
+        format!(
+            "w{writer} n{n}\n{}\n{}",
+            fill.repeat(LONG_LINE),
+            fill.repeat(LONG_LINE)
+        )
+    } else {
+        format!("w{writer} n{n}")
+    }
+}
+
+fn nt(config: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_nt"));
+    cmd.arg("--config-file").arg(config).stdout(Stdio::null());
+    cmd
+}
+
+fn append_multiline(config: &Path, body: &str) {
+    let mut child = nt(config)
+        .arg("-m")
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("spawn nt");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(body.as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn concurrent_appends_and_rewrites_never_tear_or_lose_notes() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("notes.txt");
+    let config: PathBuf = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!(
+            "note_file = \"{}\"\ndatetime_format = \"%Y-%m-%d %H:%M:%S\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+    fs::write(&note_file, "2025-01-01 00:00:00 seed 0\n").unwrap();
+
+    let done = Arc::new(AtomicBool::new(false));
+    // Rewrites race with the appends; without the lock, a rewrite drops any note appended
+    // between its read and its rename.
+    let rewriter = {
+        let (config, done) = (config.clone(), Arc::clone(&done));
+        thread::spawn(move || {
+            let mut rewrites = 0;
+            while !done.load(Ordering::SeqCst) {
+                rewrites += 1;
+                let status = nt(&config)
+                    .args(["edit", "/^seed/", &format!("seed {rewrites}")])
+                    .status()
+                    .expect("run nt edit");
+                assert!(status.success());
+            }
+            rewrites
+        })
+    };
+    let writers: Vec<_> = (0..WRITERS)
+        .map(|writer| {
+            let config = config.clone();
+            thread::spawn(move || {
+                for n in 0..NOTES_PER_WRITER {
+                    append_multiline(&config, &note_body(writer, n));
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::SeqCst);
+    let rewrites = rewriter.join().unwrap();
+
+    let mut expected: BTreeSet<String> = (0..WRITERS)
+        .flat_map(|w| (0..NOTES_PER_WRITER).map(move |n| note_body(w, n)))
+        .collect();
+    expected.insert(format!("seed {rewrites}"));
+
+    let parser = NoteParser::new("%Y-%m-%d %H:%M:%S");
+    let entries = read_all_notes_from_file_allow_missing(&note_file, &parser)
+        .unwrap()
+        .expect("note file exists");
+    let mut seen = BTreeSet::new();
+    for entry in &entries {
+        let note = entry
+            .as_note()
+            .expect("every record starts with a timestamp");
+        assert!(
+            seen.insert(note.text.clone()),
+            "duplicate note {:.20}",
+            note.text
+        );
+    }
+    let missing: Vec<_> = expected
+        .difference(&seen)
+        .map(|t| &t[..t.len().min(20)])
+        .collect();
+    assert!(missing.is_empty(), "lost notes: {missing:?}");
+    assert_eq!(seen, expected, "notes were torn");
+}
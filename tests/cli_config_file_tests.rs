@@ -0,0 +1,289 @@
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+#[test]
+fn config_file_flag_selects_note_file_for_append_and_print() {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("project.txt");
+    let config_path = tmp.path().join("project.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .arg("deployed")
+        .arg("v2")
+        .status()
+        .expect("run nt");
+    assert!(status.success());
+    let contents = fs::read_to_string(&note_file_path).unwrap();
+    assert!(contents.ends_with(" deployed v2\n"), "file was: {contents}");
+}
+
+#[test]
+fn missing_explicit_config_file_is_an_error() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("absent.toml");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .arg("--print")
+        .output()
+        .expect("run nt");
+    assert!(!output.status.success(), "expected failure exit");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("config file not found"),
+        "stderr was: {stderr}"
+    );
+}
+
+#[test]
+fn config_path_reports_explicit_config_file() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("custom.toml");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .arg("--config-path")
+        .output()
+        .expect("run nt");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), config_path.display().to_string());
+}
+
+#[test]
+fn at_flag_backdates_appended_note() {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("notes.txt");
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .args(["--at", "2024-12-31 23:30", "fixed", "the", "build"])
+        .status()
+        .expect("run nt");
+    assert!(status.success());
+    let contents = fs::read_to_string(&note_file_path).unwrap();
+    assert_eq!(contents, "2024-12-31 23:30 fixed the build\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .args(["--at", "whenever", "nope"])
+        .output()
+        .expect("run nt");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn legacy_config_is_used_when_nt_toml_is_missing() {
+    let home = TempDir::new().unwrap();
+    let config_dir = home.path().join(".config").join("nt");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config"),
+        "note_file=\"~/old/daybook.txt\"\ndatetime_format=%d.%m.%Y %H:%M\n",
+    )
+    .unwrap();
+    let old_file = home.path().join("old").join("daybook.txt");
+    fs::create_dir_all(old_file.parent().unwrap()).unwrap();
+    fs::write(&old_file, "[07.03.2025 09:05] standup\n").unwrap();
+
+    let nt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nt"))
+            .env("HOME", home.path())
+            .env_remove("XDG_CONFIG_HOME")
+            .args(args)
+            .output()
+            .expect("run nt")
+    };
+    let config_path = nt(&["--config-path"]);
+    assert_eq!(
+        String::from_utf8(config_path.stdout).unwrap(),
+        format!("{}\n", config_dir.join("config").display())
+    );
+    let import = nt(&["import", "legacy"]);
+    assert!(import.status.success(), "stderr: {:?}", import.stderr);
+    assert_eq!(
+        String::from_utf8(import.stdout).unwrap(),
+        format!("imported 1 note from {}\n", old_file.display())
+    );
+    assert_eq!(
+        fs::read_to_string(&old_file).unwrap(),
+        "2025-03-07 09:05 standup\n"
+    );
+
+    assert!(nt(&["--at", "2025-03-08 10:00", "next"]).status.success());
+    assert_eq!(
+        fs::read_to_string(&old_file).unwrap(),
+        "2025-03-07 09:05 standup\n2025-03-08 10:00 next\n"
+    );
+}
+
+#[test]
+fn legacy_datetime_format_is_not_used_for_nt_notes() {
+    let home = TempDir::new().unwrap();
+    let config_dir = home.path().join(".config").join("nt");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config"),
+        "note_file=~/daybook.txt\ndatetime_format=%H:%M\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .env("HOME", home.path())
+        .env_remove("XDG_CONFIG_HOME")
+        .args(["--at", "2025-03-08 10:00", "next"])
+        .output()
+        .expect("run nt");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    assert_eq!(
+        fs::read_to_string(home.path().join("daybook.txt")).unwrap(),
+        "2025-03-08 10:00 next\n"
+    );
+}
+
+#[test]
+fn empty_nt_config_is_ignored() {
+    let home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .env("HOME", home.path())
+        .env_remove("XDG_CONFIG_HOME")
+        .env("NT_CONFIG", "")
+        .args(["--at", "2025-03-08 10:00", "next"])
+        .output()
+        .expect("run nt");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(home.path().join("daybook.txt")).unwrap(),
+        "2025-03-08 10:00 next\n"
+    );
+}
+
+#[test]
+fn invalid_datetime_format_is_reported_instead_of_panicking() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("cfg.toml");
+    let note_file = tmp.path().join("notes.txt");
+    fs::write(
+        &config_path,
+        format!(
+            "note_file = \"{}\"\ndatetime_format = \"%Y-%m-%d %H:%Q\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .arg("hello")
+        .output()
+        .expect("run nt");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("unknown specifier %Q"),
+        "stderr was: {stderr}"
+    );
+    assert!(!stderr.contains("panicked"), "stderr was: {stderr}");
+    assert!(!note_file.exists());
+}
+
+#[test]
+fn environment_variables_override_the_config_file() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("cfg.toml");
+    let file_notes = tmp.path().join("file.txt");
+    let env_notes = tmp.path().join("env.txt");
+    fs::write(
+        &config_path,
+        format!(
+            "note_file = \"{}\"\ndurability = \"fsync\"\n",
+            file_notes.display()
+        ),
+    )
+    .unwrap();
+    let nt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nt"))
+            .env("NT_CONFIG", &config_path)
+            .env("NT_NOTE_FILE", &env_notes)
+            .env("NT_DATETIME_FORMAT", "%d.%m.%Y %H:%M")
+            .env("NT_TIMEZONE", "UTC")
+            .env_remove("NT_DURABILITY")
+            .env_remove("NT_FORMAT")
+            .args(args)
+            .output()
+            .expect("run nt")
+    };
+
+    let output = nt(&["--at", "2025-03-07 09:00", "from", "ci"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(!file_notes.exists());
+    assert_eq!(
+        fs::read_to_string(&env_notes).unwrap(),
+        "07.03.2025 09:00 from ci\n"
+    );
+
+    let shown = nt(&["config", "show", "--origin"]);
+    assert!(shown.status.success(), "{shown:?}");
+    assert_eq!(
+        String::from_utf8(shown.stdout).unwrap(),
+        format!(
+            "note_file = \"{}\"  # environment NT_NOTE_FILE\n\
+             datetime_format = \"%d.%m.%Y %H:%M\"  # environment NT_DATETIME_FORMAT\n\
+             durability = \"fsync\"  # file {}\n\
+             format = \"text\"  # default\n\
+             timezone = \"UTC\"  # environment NT_TIMEZONE\n",
+            env_notes.display(),
+            config_path.display()
+        )
+    );
+    let plain = String::from_utf8(nt(&["config", "show"]).stdout).unwrap();
+    assert!(plain.contains("format = \"text\"\n"), "{plain}");
+    assert!(!plain.contains('#'), "{plain}");
+
+    let path = nt(&["--config-path"]);
+    assert_eq!(
+        String::from_utf8(path.stdout).unwrap().trim_end(),
+        config_path.display().to_string()
+    );
+}
+
+#[test]
+fn invalid_environment_value_names_the_variable() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", tmp.path().join("n.txt").display()),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .env("NT_DURABILITY", "sometimes")
+        .args(["config", "show"])
+        .output()
+        .expect("run nt");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("NT_DURABILITY"), "stderr was: {stderr}");
+    assert!(stderr.contains("sometimes"), "stderr was: {stderr}");
+}
@@ -1,8 +1,13 @@
 use std::process::Command;
+use tempfile::TempDir;
 
 #[test]
 fn config_path_flag_prints_default_path_and_exits() {
+    // A home without the bash script's config, whose path would be printed instead.
+    let home = TempDir::new().unwrap();
     let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .env("HOME", home.path())
+        .env_remove("XDG_CONFIG_HOME")
         .arg("--config-path")
         .output()
         .expect("run nt");
@@ -0,0 +1,159 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+const NOTES: &str = "2025-03-06 17:00 first\n2025-03-07 09:00 standup notes\n\tmore detail\n2025-03-07 16:30 deplyoed api #infra\n";
+
+fn setup() -> (TempDir, PathBuf, PathBuf) {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("notes.txt");
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+    fs::write(&note_file_path, NOTES).unwrap();
+    (tmp, config_path, note_file_path)
+}
+
+fn nt(config_path: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_nt"));
+    cmd.arg("--config-file").arg(config_path).args(args);
+    cmd
+}
+
+#[test]
+fn amend_replaces_last_note_text_and_keeps_timestamp() {
+    let (_tmp, cfg, notes) = setup();
+    let output = nt(&cfg, &["amend", "deployed", "api", "#infra"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "status: {:?}", output.status);
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        NOTES.replace("deplyoed", "deployed")
+    );
+}
+
+#[test]
+fn edit_selector_opens_editor_on_older_note() {
+    let (tmp, cfg, notes) = setup();
+    let editor = tmp.path().join("editor.sh");
+    fs::write(
+        &editor,
+        "#!/bin/sh\nsed -i 's/more detail/action items: none/' \"$1\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = nt(&cfg, &["edit", "@2"])
+        .env_remove("VISUAL")
+        .env("EDITOR", &editor)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "status: {:?}", output.status);
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        NOTES.replace("more detail", "action items: none")
+    );
+}
+
+#[test]
+fn edit_out_of_range_selector_fails_without_touching_file() {
+    let (_tmp, cfg, notes) = setup();
+    let output = nt(&cfg, &["edit", "@9", "x"]).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(fs::read_to_string(&notes).unwrap(), NOTES);
+
+    let output = nt(&cfg, &["edit", "9", "x"]).output().unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "bad selector is a usage error"
+    );
+}
+
+#[test]
+fn rewrite_preserves_file_permissions_and_leaves_no_temp_file() {
+    let (tmp, cfg, notes) = setup();
+    fs::set_permissions(&notes, fs::Permissions::from_mode(0o600)).unwrap();
+    let output = nt(&cfg, &["amend", "fixed"]).output().unwrap();
+    assert!(output.status.success());
+    let mode = fs::metadata(&notes).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode, 0o600);
+    let leftovers: Vec<_> = fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.contains(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "leftover temp files: {leftovers:?}");
+}
+
+#[test]
+fn rm_asks_for_confirmation_and_undo_restores() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let (_tmp, cfg, notes) = setup();
+    let mut child = nt(&cfg, &["rm", "@2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.as_mut().unwrap().write_all(b"n\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success(), "declined prompt should fail");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("standup notes"), "stderr was: {stderr}");
+    assert_eq!(fs::read_to_string(&notes).unwrap(), NOTES);
+
+    let output = nt(&cfg, &["rm", "--yes", "--match", "STANDUP"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "2025-03-06 17:00 first\n2025-03-07 16:30 deplyoed api #infra\n"
+    );
+
+    let output = nt(&cfg, &["rm", "-y"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "2025-03-06 17:00 first\n"
+    );
+
+    assert!(nt(&cfg, &["undo"]).status().unwrap().success());
+    assert!(nt(&cfg, &["undo"]).status().unwrap().success());
+    assert_eq!(fs::read_to_string(&notes).unwrap(), NOTES);
+    assert!(!nt(&cfg, &["undo"]).status().unwrap().success());
+}
+
+#[test]
+fn edit_and_rm_accept_id_and_regex_selectors() {
+    let (_tmp, cfg, notes) = setup();
+    let printed = nt(&cfg, &["-p", "3"]).output().unwrap().stdout;
+    let printed = String::from_utf8(printed).unwrap();
+    let first_id = printed.lines().next().unwrap().split(' ').next().unwrap();
+
+    let selector = format!("#{}", &first_id[..5]);
+    assert!(
+        nt(&cfg, &["edit", &selector, "first!"])
+            .status()
+            .unwrap()
+            .success()
+    );
+    assert!(
+        nt(&cfg, &["rm", "-y", "/^standup/"])
+            .status()
+            .unwrap()
+            .success()
+    );
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "2025-03-06 17:00 first!\n2025-03-07 16:30 deplyoed api #infra\n"
+    );
+}
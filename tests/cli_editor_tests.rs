@@ -0,0 +1,72 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, PathBuf, PathBuf) {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("notes.txt");
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+    (tmp, config_path, note_file_path)
+}
+
+/// Writes an executable shell script that acts as the editor.
+fn stub_editor(dir: &Path, body: &str) -> PathBuf {
+    let path = dir.join("stub-editor.sh");
+    fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn run_with_editor(config_path: &Path, editor: &Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(config_path)
+        .args(args)
+        .env_remove("VISUAL")
+        .env("EDITOR", editor)
+        .output()
+        .expect("run nt")
+}
+
+#[test]
+fn edit_new_appends_buffer_without_template_comments() {
+    let (tmp, cfg, notes) = setup();
+    // Prepend two lines to the template, keeping its "#:" comment lines.
+    let editor = stub_editor(
+        tmp.path(),
+        r#"{ printf 'design review #arch\n  - split the api\n'; cat "$1"; } > "$1.new" && mv "$1.new" "$1""#,
+    );
+    let output = run_with_editor(&cfg, &editor, &["-e"]);
+    assert!(output.status.success(), "status: {:?}", output.status);
+    let contents = fs::read_to_string(&notes).unwrap();
+    assert!(
+        contents.ends_with(" design review #arch\n\t  - split the api\n"),
+        "file was: {contents:?}"
+    );
+}
+
+#[test]
+fn edit_new_aborts_when_buffer_is_left_unchanged() {
+    let (tmp, cfg, notes) = setup();
+    let editor = stub_editor(tmp.path(), "exit 0");
+    let output = run_with_editor(&cfg, &editor, &["--edit-new"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("aborting"), "stderr was: {stderr}");
+    assert!(!notes.exists(), "note file should not be created");
+}
+
+#[test]
+fn edit_new_reports_failing_editor() {
+    let (tmp, cfg, notes) = setup();
+    let editor = stub_editor(tmp.path(), "exit 3");
+    let output = run_with_editor(&cfg, &editor, &["-e"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!notes.exists());
+}
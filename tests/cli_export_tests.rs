@@ -0,0 +1,47 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn export_filters_and_writes_to_output_file() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!("note_file = \"{}\"\n", note_file.display()),
+    )
+    .unwrap();
+    fs::write(
+        &note_file,
+        "2025-03-06 17:00 old\n2025-03-07 09:00 standup #team\n2025-03-07 12:00 lunch\n",
+    )
+    .unwrap();
+    let output = tmp.path().join("week.md");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config)
+        .args([
+            "export",
+            "--format",
+            "md",
+            "--since",
+            "2025-03-07",
+            "--tag",
+            "team",
+            "--output",
+        ])
+        .arg(&output)
+        .output()
+        .expect("run nt");
+    assert!(result.status.success());
+    assert_eq!(
+        String::from_utf8(result.stdout).unwrap(),
+        format!("exported 1 note to {}\n", output.display())
+    );
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        "## 2025-03-07\n\n- 09:00 standup #team\n"
+    );
+}
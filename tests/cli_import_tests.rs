@@ -0,0 +1,140 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn nt(config: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(config)
+        .args(args)
+        .output()
+        .expect("run nt")
+}
+
+#[test]
+fn import_merges_into_the_daybook_and_skips_duplicates_on_rerun() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!("note_file = \"{}\"\n", note_file.display()),
+    )
+    .unwrap();
+    fs::write(&note_file, "2025-03-07 12:00 lunch\n").unwrap();
+    let export = tmp.path().join("log.md");
+    fs::write(
+        &export,
+        "## 2025-03-07\n- 09:00 standup\n- 17:40 wrap up\nstray\n\n## 2025-03-06\n- 18:00 plan\n",
+    )
+    .unwrap();
+    let path = export.to_str().unwrap();
+
+    let first = nt(&config, &["import", "markdown", path]);
+    assert!(first.status.success());
+    assert_eq!(
+        String::from_utf8(first.stdout).unwrap(),
+        format!("imported 3 notes from {path}\n")
+    );
+    assert_eq!(
+        fs::read_to_string(&note_file).unwrap(),
+        "2025-03-06 18:00 plan\n2025-03-07 09:00 standup\n2025-03-07 12:00 lunch\n2025-03-07 17:40 wrap up\n\tstray\n"
+    );
+
+    let second = nt(&config, &["import", "markdown", path]);
+    assert!(second.status.success());
+    assert_eq!(
+        String::from_utf8(second.stdout).unwrap(),
+        format!("imported 0 notes from {path} (3 duplicates skipped)\n")
+    );
+}
+
+#[test]
+fn import_csv_reports_rows_without_a_timestamp() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!("note_file = \"{}\"\n", note_file.display()),
+    )
+    .unwrap();
+    let export = tmp.path().join("log.csv");
+    fs::write(&export, "date,note\n2025-03-07 09:00,standup\nsoon,later\n").unwrap();
+
+    let output = nt(
+        &config,
+        &[
+            "import",
+            "csv",
+            export.to_str().unwrap(),
+            "--timestamp-column",
+            "date",
+            "--text-column",
+            "note",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "line 3: no timestamp, skipped: soon,later\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&note_file).unwrap(),
+        "2025-03-07 09:00 standup\n"
+    );
+}
+
+#[test]
+fn imported_timestamps_are_read_in_the_configured_timezone() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!(
+            "note_file = \"{}\"\ntimezone = \"UTC\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+    let nt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nt"))
+            .arg("--config-file")
+            .arg(&config)
+            .args(args)
+            .env("TZ", "Europe/Berlin")
+            .output()
+            .expect("run nt")
+    };
+    assert!(
+        nt(&["--at", "2025-03-07 09:30", "standup"])
+            .status
+            .success()
+    );
+    let csv = tmp.path().join("log.csv");
+    fs::write(&csv, "date,note\n2025-03-07 10:00,review\n").unwrap();
+    let markdown = tmp.path().join("log.md");
+    fs::write(&markdown, "## 2025-03-07\n- 09:00 coffee\n").unwrap();
+
+    let csv = nt(&[
+        "import",
+        "csv",
+        csv.to_str().unwrap(),
+        "--timestamp-column",
+        "date",
+        "--text-column",
+        "note",
+    ]);
+    assert!(csv.status.success());
+    assert!(
+        nt(&["import", "markdown", markdown.to_str().unwrap()])
+            .status
+            .success()
+    );
+    assert_eq!(
+        fs::read_to_string(&note_file).unwrap(),
+        "2025-03-07 09:00 coffee\n2025-03-07 09:30 standup\n2025-03-07 10:00 review\n"
+    );
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn nt(config: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(config)
+        .args(args)
+        .output()
+        .expect("run nt")
+}
+
+#[test]
+fn jsonl_backend_appends_prints_searches_and_edits() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("notes.jsonl");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!(
+            "note_file = \"{}\"\nformat = \"jsonl\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+
+    assert!(
+        nt(
+            &config,
+            &["--at", "2025-03-07T11:00:00+01:00", "review #ops"]
+        )
+        .status
+        .success()
+    );
+    assert!(
+        nt(&config, &["--at", "2025-03-07T16:30:00+01:00", "deploy"])
+            .status
+            .success()
+    );
+    let contents = fs::read_to_string(&note_file).unwrap();
+    assert_eq!(
+        contents,
+        concat!(
+            r#"{"ts":"2025-03-07T11:00:00+01:00","text":"review #ops","tags":["ops"],"id":"c21cc115"}"#,
+            "\n",
+            r#"{"ts":"2025-03-07T16:30:00+01:00","text":"deploy","tags":[],"id":"c12750aa"}"#,
+            "\n"
+        )
+    );
+
+    let print = nt(&config, &["-p"]);
+    assert!(print.status.success());
+    assert_eq!(
+        String::from_utf8(print.stdout).unwrap(),
+        "c21cc115 2025-03-07 11:00 review #ops\nc12750aa 2025-03-07 16:30 deploy\n"
+    );
+
+    let search = nt(&config, &["search", "REVIEW"]);
+    assert_eq!(
+        String::from_utf8(search.stdout).unwrap(),
+        "2025-03-07 11:00 review #ops\n"
+    );
+
+    assert!(
+        nt(&config, &["edit", "#c12750aa", "deployed", "#infra"])
+            .status
+            .success()
+    );
+    let last = fs::read_to_string(&note_file)
+        .unwrap()
+        .lines()
+        .last()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        last,
+        r#"{"ts":"2025-03-07T16:30:00+01:00","text":"deployed #infra","tags":["infra"],"id":"03caad8a"}"#
+    );
+}
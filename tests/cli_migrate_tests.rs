@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn nt(config: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nt"))
+        .env("TZ", "UTC")
+        .arg("--config-file")
+        .arg(config)
+        .args(args)
+        .output()
+        .expect("run nt")
+}
+
+#[test]
+fn migrate_writes_next_to_the_note_file_and_leaves_config_alone() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    let config_contents = format!("note_file = \"{}\"\n", note_file.display());
+    fs::write(&config, &config_contents).unwrap();
+    fs::write(
+        &note_file,
+        "2025-03-07 09:00 standup #team\nstray line\n2025-03-07 16:30 deploy\n\tdetails\n",
+    )
+    .unwrap();
+
+    let output = nt(&config, &["migrate", "--to", "jsonl"]);
+    assert!(output.status.success());
+    let migrated = tmp.path().join("daybook.jsonl");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("migrated 3 notes to {}\n", migrated.display())
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("record 2: unparseable timestamp, copied as is: stray line"),
+        "stderr was: {stderr}"
+    );
+    assert_eq!(
+        fs::read_to_string(&migrated).unwrap(),
+        concat!(
+            r#"{"ts":"2025-03-07T09:00:00+00:00","text":"standup #team","tags":["team"],"id":"503c98dd"}"#,
+            "\n",
+            "stray line\n",
+            r#"{"ts":"2025-03-07T16:30:00+00:00","text":"deploy\ndetails","tags":[],"id":"4295a8c2"}"#,
+            "\n"
+        )
+    );
+    assert_eq!(fs::read_to_string(&config).unwrap(), config_contents);
+
+    let again = nt(&config, &["migrate", "--to", "jsonl"]);
+    assert_eq!(again.status.code(), Some(1));
+    assert!(
+        String::from_utf8(again.stderr)
+            .unwrap()
+            .contains("already contains notes")
+    );
+}
+
+#[test]
+fn migrate_save_config_switches_to_the_new_store() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!("note_file = \"{}\"\n", note_file.display()),
+    )
+    .unwrap();
+    fs::write(&note_file, "2025-03-07 09:00 standup\n").unwrap();
+    let before = String::from_utf8(nt(&config, &["-p"]).stdout).unwrap();
+
+    let output = tmp.path().join("moved.jsonl");
+    let result = nt(
+        &config,
+        &[
+            "migrate",
+            "--to",
+            "jsonl",
+            "--output",
+            output.to_str().unwrap(),
+            "--save-config",
+        ],
+    );
+    assert!(result.status.success());
+    assert_eq!(
+        fs::read_to_string(&config).unwrap(),
+        format!("note_file = \"{}\"\nformat = \"jsonl\"\n", output.display())
+    );
+    assert_eq!(
+        String::from_utf8(nt(&config, &["-p"]).stdout).unwrap(),
+        before
+    );
+}
+
+#[test]
+fn migrate_save_config_keeps_environment_overrides_out_of_the_file() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!(
+            "note_file = \"{}\"\ndurability = \"fsync\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+    fs::write(&note_file, "2025-03-07 09:00 standup\n").unwrap();
+
+    let output = tmp.path().join("moved.jsonl");
+    let result = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .env("TZ", "UTC")
+        .env("NT_DURABILITY", "flush")
+        .env("NT_TIMEZONE", "Asia/Tokyo")
+        .arg("--config-file")
+        .arg(&config)
+        .args([
+            "migrate",
+            "--to",
+            "jsonl",
+            "--output",
+            output.to_str().unwrap(),
+            "--save-config",
+        ])
+        .output()
+        .expect("run nt");
+    assert!(result.status.success());
+    assert_eq!(
+        fs::read_to_string(&config).unwrap(),
+        format!(
+            "note_file = \"{}\"\ndurability = \"fsync\"\nformat = \"jsonl\"\n",
+            output.display()
+        )
+    );
+}
+
+#[test]
+fn migrate_refuses_to_overwrite_the_current_note_file() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!("note_file = \"{}\"\n", note_file.display()),
+    )
+    .unwrap();
+    fs::write(&note_file, "2025-03-07 09:00 standup\n").unwrap();
+
+    let output = nt(&config, &["migrate", "--to", "text"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        fs::read_to_string(&note_file).unwrap(),
+        "2025-03-07 09:00 standup\n"
+    );
+}
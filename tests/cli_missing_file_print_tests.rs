@@ -2,7 +2,7 @@ use std::{fs, process::Command};
 use tempfile::TempDir;
 
 #[test]
-fn print_when_file_missing_shows_friendly_message() {
+fn print_when_file_missing_reports_on_stderr_with_distinct_exit_code() {
     let tmp = TempDir::new().expect("tempdir");
     let note_file_path = tmp.path().join("notes.txt");
     let config_path = tmp.path().join("cfg.toml");
@@ -11,21 +11,20 @@ fn print_when_file_missing_shows_friendly_message() {
 
     assert!(!note_file_path.exists(), "note file should not exist yet");
 
-    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
-        .arg("--config-file")
-        .arg(config_path.as_os_str())
-        .arg("--print")
-        .output()
-        .expect("failed to run nt binary");
+    for args in [&["--print"][..], &["search", "x", "--json"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+            .arg("--config-file")
+            .arg(config_path.as_os_str())
+            .args(args)
+            .output()
+            .expect("failed to run nt binary");
 
-    assert!(
-        output.status.success(),
-        "expected success exit, got {:?}",
-        output.status
-    );
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    assert!(
-        stdout.contains("no notes have been made"),
-        "stdout was: {stdout}"
-    );
+        assert_eq!(output.status.code(), Some(3), "args: {args:?}");
+        assert!(output.stdout.is_empty(), "stdout must stay clean");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("no notes have been made"),
+            "stderr was: {stderr}"
+        );
+    }
 }
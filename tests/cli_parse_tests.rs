@@ -1,11 +1,23 @@
 use clap::Parser;
-use nt::cli::Cli;
+use nt::cli::{Cli, CommandAction};
 
 #[test]
 fn parse_print_default_count() {
     let cli = Cli::try_parse_from(["nt", "-p"]).unwrap();
-    assert!(cli.print.is_some());
-    assert_eq!(cli.print.unwrap(), Some(10));
+    assert_eq!(cli.print, Some(None));
+    assert!(matches!(
+        cli.into_action().unwrap(),
+        CommandAction::Print {
+            count: Some(10),
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["nt", "-p", "--since", "2025-03-07"]).unwrap();
+    assert!(matches!(
+        cli.into_action().unwrap(),
+        CommandAction::Print { count: None, .. }
+    ));
 }
 
 #[test]
@@ -20,3 +32,14 @@ fn parse_append_text() {
     assert!(cli.print.is_none());
     assert_eq!(cli.note, vec!["hello", "world"]);
 }
+
+#[test]
+fn parse_search_subcommand_keeps_note_text_parsing_intact() {
+    let cli = Cli::try_parse_from(["nt", "search", "deploy", "--word"]).unwrap();
+    assert!(cli.command.is_some());
+    assert!(cli.note.is_empty());
+
+    let cli = Cli::try_parse_from(["nt", "researching", "search"]).unwrap();
+    assert!(cli.command.is_none());
+    assert_eq!(cli.note, vec!["researching", "search"]);
+}
@@ -0,0 +1,78 @@
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+#[test]
+fn print_on_date_combines_with_count() {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("notes.txt");
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+    fs::write(
+        &note_file_path,
+        "2025-03-06 17:00 thursday\n2025-03-07 09:00 standup\n2025-03-07 11:00 review\n2025-03-07 16:30 deploy\n2025-03-08 10:00 saturday\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&config_path)
+        .args(["-p", "2", "--on", "2025-03-07"])
+        .output()
+        .expect("run nt");
+    assert!(output.status.success(), "status: {:?}", output.status);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout, "cf18223c 2025-03-07 11:00 review\nc12750aa 2025-03-07 16:30 deploy\n",
+        "stdout was: {stdout}"
+    );
+}
+
+#[test]
+fn range_flags_without_print_are_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .args(["--since", "2025-03-07"])
+        .output()
+        .expect("run nt");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn print_with_a_range_and_no_count_prints_every_note_in_it() {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("notes.txt");
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+    let mut contents = String::from("2025-03-06 17:00 thursday\n");
+    for hour in 8..20 {
+        contents.push_str(&format!("2025-03-07 {hour:02}:00 note {hour}\n"));
+    }
+    fs::write(&note_file_path, contents).unwrap();
+    let print = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+            .arg("--config-file")
+            .arg(&config_path)
+            .args(args)
+            .output()
+            .expect("run nt");
+        assert!(output.status.success(), "status: {:?}", output.status);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let since = print(&["-p", "--since", "2025-03-07"]);
+    assert_eq!(since.lines().count(), 12, "stdout was: {since}");
+    assert!(since.lines().next().unwrap().ends_with("08:00 note 8"));
+    assert_eq!(print(&["-p", "--on", "2025-03-07"]), since);
+    assert_eq!(print(&["-p"]).lines().count(), 10);
+    assert_eq!(
+        print(&["-p", "3", "--since", "2025-03-07"]).lines().count(),
+        3
+    );
+}
@@ -0,0 +1,218 @@
+use std::{fs, path::PathBuf, process::Command};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, PathBuf) {
+    let tmp = TempDir::new().unwrap();
+    let note_file_path = tmp.path().join("notes.txt");
+    let config_path = tmp.path().join("cfg.toml");
+    fs::write(
+        &config_path,
+        format!("note_file = \"{}\"\n", note_file_path.display()),
+    )
+    .unwrap();
+    fs::write(
+        &note_file_path,
+        "2025-03-06 17:00 Deploy prep\n2025-03-07 09:00 standup\n2025-03-07 16:30 deployed api\n",
+    )
+    .unwrap();
+    (tmp, config_path)
+}
+
+fn run(config_path: &PathBuf, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .expect("run nt");
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn search_subcommand_prints_matching_notes_with_timestamps() {
+    let (_tmp, cfg) = setup();
+    let (ok, stdout) = run(&cfg, &["search", "deploy"]);
+    assert!(ok);
+    assert_eq!(
+        stdout,
+        "2025-03-06 17:00 Deploy prep\n2025-03-07 16:30 deployed api\n"
+    );
+}
+
+#[test]
+fn short_search_flag_supports_word_mode_and_count() {
+    let (_tmp, cfg) = setup();
+    let (ok, stdout) = run(&cfg, &["-s", "deploy", "--word", "--count"]);
+    assert!(ok);
+    assert_eq!(stdout, "1\n");
+}
+
+#[test]
+fn search_combines_with_date_range() {
+    let (_tmp, cfg) = setup();
+    let (ok, stdout) = run(
+        &cfg,
+        &["search", "--regex", "^[Dd]eploy", "--on", "2025-03-07"],
+    );
+    assert!(ok);
+    assert_eq!(stdout, "2025-03-07 16:30 deployed api\n");
+}
+
+#[test]
+fn invalid_regex_is_a_usage_error() {
+    let (_tmp, cfg) = setup();
+    let output = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&cfg)
+        .args(["search", "--regex", "("])
+        .output()
+        .expect("run nt");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn tags_subcommand_lists_counts_and_search_filters_by_tag() {
+    let (tmp, cfg) = setup();
+    fs::write(
+        tmp.path().join("notes.txt"),
+        "2025-03-06 17:00 deploy prep #infra\n2025-03-07 09:00 invoices #billing\n2025-03-07 16:30 deployed #infra #billing\n",
+    )
+    .unwrap();
+    let (ok, stdout) = run(&cfg, &["tags"]);
+    assert!(ok);
+    assert_eq!(stdout, "#billing\t2\t2025-03-07\n#infra\t2\t2025-03-07\n");
+
+    let (ok, stdout) = run(&cfg, &["search", "deploy", "--tag", "billing"]);
+    assert!(ok);
+    assert_eq!(stdout, "2025-03-07 16:30 deployed #infra #billing\n");
+
+    let (ok, stdout) = run(&cfg, &["-p", "--tag", "#infra"]);
+    assert!(ok);
+    assert_eq!(
+        stdout,
+        "72508cd4 2025-03-06 17:00 deploy prep #infra\nc6ff7d93 2025-03-07 16:30 deployed #infra #billing\n"
+    );
+}
+
+#[test]
+fn print_and_search_support_json_and_templates() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("notes.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!("note_file = \"{}\"\n", note_file.display()),
+    )
+    .unwrap();
+    fs::write(
+        &note_file,
+        "2025-03-07 09:00 standup #team\n2025-03-07 12:00 lunch\n",
+    )
+    .unwrap();
+    let nt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nt"))
+            .arg("--config-file")
+            .arg(&config)
+            .args(args)
+            .output()
+            .expect("run nt")
+    };
+
+    let print = nt(&["-p", "--format", r"{time}\t{text}"]);
+    assert!(print.status.success());
+    assert_eq!(
+        String::from_utf8(print.stdout).unwrap(),
+        "2025-03-07 09:00\tstandup #team\n2025-03-07 12:00\tlunch\n"
+    );
+
+    let search = nt(&["search", "standup", "--json"]);
+    let stdout = String::from_utf8(search.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1);
+    let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(value["text"], "standup #team");
+
+    let bad = nt(&["-s", "x", "--format", "{nope}"]);
+    assert_eq!(bad.status.code(), Some(2));
+    assert!(nt(&["--json"]).status.code() == Some(2));
+}
+
+#[test]
+fn configured_timezone_stamps_notes_and_tz_converts_for_display() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("notes.txt");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!(
+            "note_file = \"{}\"\ndatetime_format = \"%Y-%m-%d %H:%M %z\"\ntimezone = \"UTC\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+    let nt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nt"))
+            .arg("--config-file")
+            .arg(&config)
+            .args(args)
+            .env("TZ", "America/New_York")
+            .output()
+            .expect("run nt")
+    };
+
+    assert!(
+        nt(&["--at", "2025-03-07 09:00", "incident start"])
+            .status
+            .success()
+    );
+    assert!(nt(&["hello"]).status.success());
+    let contents = fs::read_to_string(&note_file).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines[0], "2025-03-07 09:00 +0000 incident start");
+    assert!(lines[1].ends_with(" +0000 hello"), "{contents}");
+
+    let shown = nt(&["-s", "incident", "--tz", "Asia/Tokyo"]);
+    assert_eq!(
+        String::from_utf8(shown.stdout).unwrap(),
+        "2025-03-07 18:00 +0900 incident start\n"
+    );
+    let shown = nt(&["-p", "1", "--tz", "Asia/Tokyo", "--format", "{ts}"]);
+    assert!(shown.status.success());
+    assert!(
+        String::from_utf8(shown.stdout)
+            .unwrap()
+            .ends_with("+09:00\n"),
+        "stdout should use the display zone"
+    );
+
+    let plain = tmp.path().join("plain.txt");
+    let plain_config = tmp.path().join("plain.toml");
+    fs::write(
+        &plain_config,
+        format!("note_file = \"{}\"\ntimezone = \"UTC\"\n", plain.display()),
+    )
+    .unwrap();
+    let added = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&plain_config)
+        .args(["--at", "2025-03-07T09:00:00+09:00", "from tokyo"])
+        .env("TZ", "America/New_York")
+        .output()
+        .expect("run nt");
+    assert!(added.status.success());
+    assert_eq!(
+        fs::read_to_string(&plain).unwrap(),
+        "2025-03-07 00:00 from tokyo\n",
+        "offset-less timestamps are written in the configured zone"
+    );
+
+    let bad = nt(&["-p", "--tz", "Mars/Olympus"]);
+    assert_eq!(bad.status.code(), Some(2));
+    assert!(
+        String::from_utf8(bad.stderr)
+            .unwrap()
+            .contains("unknown timezone")
+    );
+}
@@ -128,3 +128,45 @@ fn append_from_stdin_reports_added_count() {
     assert!(printed.contains("line b"));
     assert!(printed.contains("line c"));
 }
+
+#[test]
+fn multiline_flag_stores_all_of_stdin_as_one_note() {
+    let tmp_dir = TempDir::new().unwrap();
+    let note_file_path = tmp_dir.path().join("notes.txt");
+    let (_cfg_handle, cfg_path) = temp_config(&note_file_path);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&cfg_path)
+        .arg("-m")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn nt");
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        write!(stdin, "\nretro notes\n- went well\n\n- to improve\n").unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "expected success");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("added 1 note"), "stdout was: {stdout}");
+
+    let contents = fs::read_to_string(&note_file_path).unwrap();
+    assert!(
+        contents.ends_with(" retro notes\n\t- went well\n\t\n\t- to improve\n"),
+        "file was: {contents:?}"
+    );
+
+    let print_out = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&cfg_path)
+        .arg("--print")
+        .arg("1")
+        .output()
+        .expect("print run");
+    let printed = String::from_utf8(print_out.stdout).unwrap();
+    let (id, record) = printed.split_once(' ').unwrap();
+    assert_eq!(id.len(), 8, "expected note id prefix: {printed}");
+    assert_eq!(record, contents, "last note should be the whole record");
+}
@@ -1,9 +1,11 @@
 use std::path::PathBuf;
 
 use nt::config::{
-    DEFAULT_DATETIME_FORMAT_PATTERN, DEFAULT_NOTE_FILE_LITERAL, RuntimeConfig,
+    ConfigLoadSaveError, DEFAULT_DATETIME_FORMAT_PATTERN, DEFAULT_NOTE_FILE_LITERAL, RuntimeConfig,
     default_config_file_path, expand_leading_tilde_literal, serialize_diff_from_default,
 };
+use nt::notes::{Durability, StorageFormat};
+use nt::time::Timezone;
 
 fn fake_home() -> PathBuf {
     PathBuf::from("/home/testuser")
@@ -73,6 +75,9 @@ fn serializes_only_non_default_fields_to_toml() {
         configured_note_file_literal: DEFAULT_NOTE_FILE_LITERAL.to_string(),
         expanded_note_file_path: PathBuf::from("/home/testuser/daybook.txt"),
         datetime_format_pattern: "%Y".to_string(),
+        durability: Durability::Flush,
+        format: StorageFormat::Text,
+        timezone: Timezone::Local,
     };
     let toml = serialize_diff_from_default(&cfg).unwrap();
     assert!(toml.contains("datetime_format = \"%Y\""));
@@ -85,6 +90,9 @@ fn round_trips_config_by_serializing_and_reparsing() {
         configured_note_file_literal: "~/n.txt".into(),
         expanded_note_file_path: PathBuf::from("/home/testuser/n.txt"),
         datetime_format_pattern: "%Y-%m".into(),
+        durability: Durability::Flush,
+        format: StorageFormat::Text,
+        timezone: Timezone::Local,
     };
     let toml = serialize_diff_from_default(&initial).unwrap();
     let reparsed = build_runtime_config_from_test_toml_manual_parse(&toml);
@@ -138,3 +146,110 @@ fn default_config_path_print_matches_helper() {
     // Just ensure it ends with CONFIG_FILE_NAME
     assert!(path.ends_with("nt.toml"));
 }
+
+#[test]
+fn durability_defaults_to_flush_and_is_read_from_toml() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("nt.toml");
+    std::fs::write(&path, "note_file = \"/tmp/n.txt\"\n").unwrap();
+    assert_eq!(
+        RuntimeConfig::load_from_explicit_path(&path)
+            .unwrap()
+            .durability,
+        Durability::Flush
+    );
+
+    std::fs::write(&path, "durability = \"fsync\"\n").unwrap();
+    let cfg = RuntimeConfig::load_from_explicit_path(&path).unwrap();
+    assert_eq!(cfg.durability, Durability::Fsync);
+    assert!(
+        serialize_diff_from_default(&cfg)
+            .unwrap()
+            .contains("durability = \"fsync\"")
+    );
+
+    std::fs::write(&path, "durability = \"sometimes\"\n").unwrap();
+    let err = RuntimeConfig::load_from_explicit_path(&path).unwrap_err();
+    assert!(err.to_string().contains("durability"), "{err}");
+}
+
+#[test]
+fn datetime_format_is_validated_on_load() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("nt.toml");
+    for pattern in [
+        "%Y-%m-%d %H:%M:%S",
+        "%F %R",
+        "[%d.%m.%Y %-H:%M %z]",
+        "%Y-%m-%d",
+    ] {
+        std::fs::write(&path, format!("datetime_format = \"{pattern}\"\n")).unwrap();
+        let cfg = RuntimeConfig::load_from_explicit_path(&path).unwrap();
+        assert_eq!(cfg.datetime_format_pattern, pattern);
+    }
+
+    for (pattern, problem) in [
+        ("%Y-%m-%d %Q", "unknown specifier %Q"),
+        ("%Y-%m-%d %H:%", "unknown specifier %"),
+        ("%Y-%m-%d %H:%M %#z", "specifier %#z can only be parsed"),
+        ("%H:%M", "cannot be read back"),
+        ("%Y-%m", "cannot be read back"),
+    ] {
+        std::fs::write(&path, format!("datetime_format = \"{pattern}\"\n")).unwrap();
+        let err = RuntimeConfig::load_from_explicit_path(&path).unwrap_err();
+        assert!(
+            matches!(err, ConfigLoadSaveError::InvalidDatetimeFormat { .. }),
+            "{err}"
+        );
+        assert!(err.to_string().contains(problem), "{pattern}: {err}");
+    }
+}
+
+#[test]
+fn timezone_is_read_from_toml_and_applied_to_the_parser() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("nt.toml");
+    std::fs::write(&path, "note_file = \"/tmp/n.txt\"\n").unwrap();
+    assert_eq!(
+        RuntimeConfig::load_from_explicit_path(&path)
+            .unwrap()
+            .timezone,
+        Timezone::Local
+    );
+
+    std::fs::write(&path, "timezone = \"Europe/Berlin\"\n").unwrap();
+    let cfg = RuntimeConfig::load_from_explicit_path(&path).unwrap();
+    assert_eq!(cfg.timezone, "Europe/Berlin".parse().unwrap());
+    assert_eq!(cfg.note_parser().timezone(), cfg.timezone);
+    assert!(
+        serialize_diff_from_default(&cfg)
+            .unwrap()
+            .contains("timezone = \"Europe/Berlin\"")
+    );
+
+    std::fs::write(&path, "timezone = \"Europe/Atlantis\"\n").unwrap();
+    let err = RuntimeConfig::load_from_explicit_path(&path).unwrap_err();
+    assert!(
+        matches!(err, ConfigLoadSaveError::InvalidTimezone(_)),
+        "{err}"
+    );
+    assert!(err.to_string().contains("Europe/Atlantis"), "{err}");
+}
+
+#[test]
+fn load_from_path_gives_defaults_for_a_missing_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let missing = dir.path().join("nt.toml");
+    let cfg = RuntimeConfig::load_from_path(&missing).unwrap();
+    assert_eq!(cfg.datetime_format_pattern, DEFAULT_DATETIME_FORMAT_PATTERN);
+    assert!(matches!(
+        RuntimeConfig::load_from_explicit_path(&missing),
+        Err(ConfigLoadSaveError::MissingConfigFile(_))
+    ));
+
+    std::fs::write(&missing, "durability = \"fsync\"\n").unwrap();
+    assert_eq!(
+        RuntimeConfig::load_from_path(&missing).unwrap().durability,
+        Durability::Fsync
+    );
+}
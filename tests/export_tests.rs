@@ -0,0 +1,117 @@
+use chrono::DateTime;
+use nt::export::{ExportFormat, write_export};
+use nt::import::{CsvImporter, MarkdownImporter, NoteImporter};
+use nt::notes::{Note, NoteEntry};
+use nt::time::Timezone;
+
+fn entries() -> Vec<NoteEntry> {
+    let note = |ts: &str, text: &str| {
+        NoteEntry::Parsed(Note::new(
+            DateTime::parse_from_rfc3339(ts).unwrap(),
+            text.to_string(),
+        ))
+    };
+    vec![
+        note(
+            "2025-03-07T16:30:00+01:00",
+            "deploy <v2> & #ops\nrollback plan",
+        ),
+        note("2025-03-06T09:00:00+01:00", "kickoff"),
+        note("2025-03-07T09:00:00+01:00", "standup"),
+        NoteEntry::Unparsed("stray line".into()),
+    ]
+}
+
+fn export(format: ExportFormat) -> String {
+    let mut out = Vec::new();
+    write_export(&mut out, format, &entries()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn markdown_groups_notes_under_day_headings() {
+    assert_eq!(
+        export(ExportFormat::Md),
+        "\
+## 2025-03-06
+
+- 09:00 kickoff
+
+## 2025-03-07
+
+- 09:00 standup
+- 16:30 deploy <v2> & #ops
+  rollback plan
+
+## Undated
+
+- stray line
+"
+    );
+}
+
+#[test]
+fn html_is_self_contained_and_escaped() {
+    let html = export(ExportFormat::Html);
+    assert!(html.starts_with("<!DOCTYPE html>\n"));
+    assert!(html.contains("<style>"));
+    assert!(
+        !html.contains("http"),
+        "page must not reference the network"
+    );
+    assert!(html.contains("<h2>Thursday, 2025-03-06</h2>"));
+    assert!(html.contains(
+        "<li><time datetime=\"2025-03-07T16:30:00+01:00\">16:30</time><span class=\"text\">deploy &lt;v2&gt; &amp; #ops\nrollback plan</span></li>"
+    ));
+    let friday = html.find("Friday").unwrap();
+    assert!(html[friday..].find("standup") < html[friday..].find("deploy"));
+}
+
+#[test]
+fn json_and_csv_keep_store_order_and_round_trip() {
+    let json: serde_json::Value = serde_json::from_str(&export(ExportFormat::Json)).unwrap();
+    assert_eq!(json[0]["ts"], "2025-03-07T16:30:00+01:00");
+    assert_eq!(json[0]["tags"], serde_json::json!(["ops"]));
+    assert_eq!(json[3]["ts"], serde_json::Value::Null);
+    assert_eq!(json[3]["text"], "stray line");
+
+    let csv = export(ExportFormat::Csv);
+    assert!(csv.starts_with("timestamp,text,tags,id\n"));
+    let parsed = CsvImporter {
+        timestamp_column: "timestamp".into(),
+        text_column: "text".into(),
+        timestamp_format: None,
+        pattern: "%Y-%m-%d %H:%M".into(),
+        delimiter: b',',
+        timezone: Timezone::Local,
+    }
+    .parse(&csv)
+    .unwrap();
+    let original: Vec<Note> = entries()
+        .into_iter()
+        .filter_map(|e| e.as_note().cloned())
+        .collect();
+    assert_eq!(parsed.notes, original);
+    assert_eq!(parsed.skipped.len(), 1);
+}
+
+#[test]
+fn markdown_round_trips_through_the_markdown_importer() {
+    let parsed = MarkdownImporter {
+        pattern: "%Y-%m-%d %H:%M".into(),
+        timezone: "Europe/Berlin".parse().unwrap(),
+    }
+    .parse(&export(ExportFormat::Md))
+    .unwrap();
+    let mut original: Vec<Note> = entries()
+        .into_iter()
+        .filter_map(|e| e.as_note().cloned())
+        .collect();
+    original.sort_by_key(|n| n.timestamp);
+    assert_eq!(parsed.notes, original);
+    assert_eq!(
+        parsed.skipped,
+        vec![(13, "- stray line".to_string())],
+        "undated entries are not imported"
+    );
+}
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Local, TimeZone};
+use nt::import::{
+    CsvImporter, JrnlImporter, MarkdownImporter, MergeReport, NoteImporter, merge_notes,
+};
+use nt::notes::{Note, NoteParser};
+use nt::store::{MemoryStore, NoteStore};
+use nt::time::Timezone;
+
+const PATTERN: &str = "%Y-%m-%d %H:%M";
+
+fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<chrono::FixedOffset> {
+    Local
+        .with_ymd_and_hms(y, mo, d, h, mi, 0)
+        .unwrap()
+        .fixed_offset()
+}
+
+fn summary(notes: &[Note]) -> Vec<(DateTime<chrono::FixedOffset>, &str)> {
+    notes
+        .iter()
+        .map(|n| (n.timestamp, n.text.as_str()))
+        .collect()
+}
+
+#[test]
+fn jrnl_entries_run_until_the_next_header() {
+    let input = "\
+[2025-03-07 09:00:00 AM] Standup. Talked about #release
+Body line one
+Body line two
+
+[2025-03-07 05:40:00 PM] Wrap up
+
+";
+    let parsed = JrnlImporter::default().parse(input).unwrap();
+    assert!(parsed.skipped.is_empty());
+    assert_eq!(
+        summary(&parsed.notes),
+        vec![
+            (
+                local(2025, 3, 7, 9, 0),
+                "Standup. Talked about #release\nBody line one\nBody line two"
+            ),
+            (local(2025, 3, 7, 17, 40), "Wrap up"),
+        ]
+    );
+    assert_eq!(parsed.notes[0].tags, vec!["release"]);
+
+    let custom = JrnlImporter {
+        pattern: Some("%d.%m.%Y %H:%M".into()),
+        timezone: Timezone::Local,
+    };
+    let parsed = custom
+        .parse("preamble\n07.03.2025 09:00 unbracketed\n")
+        .unwrap();
+    assert_eq!(parsed.skipped, vec![(1, "preamble".to_string())]);
+    assert_eq!(
+        summary(&parsed.notes),
+        vec![(local(2025, 3, 7, 9, 0), "unbracketed")]
+    );
+}
+
+#[test]
+fn markdown_items_under_dated_headings_become_notes() {
+    let input = "\
+# Work log
+
+## 2025-03-07
+- 09:00 standup
+- reviewed PR
+  with a second line
+1. 17:40 - wrap up
+
+A paragraph
+over two lines.
+
+## Ideas
+- not dated
+";
+    let parsed = MarkdownImporter {
+        pattern: PATTERN.into(),
+        timezone: Timezone::Local,
+    }
+    .parse(input)
+    .unwrap();
+    assert_eq!(
+        summary(&parsed.notes),
+        vec![
+            (local(2025, 3, 7, 9, 0), "standup"),
+            (local(2025, 3, 7, 0, 0), "reviewed PR\nwith a second line"),
+            (local(2025, 3, 7, 17, 40), "wrap up"),
+            (local(2025, 3, 7, 0, 0), "A paragraph\nover two lines."),
+        ]
+    );
+    assert_eq!(parsed.skipped, vec![(13, "- not dated".to_string())]);
+}
+
+#[test]
+fn csv_columns_are_chosen_by_name_or_position() {
+    let input = "id;when;body\n1;07/03/2025 09:00;standup\n2;bad date;lost\n3;07/03/2025 17:40;\"wrap; up\"\n";
+    let importer = CsvImporter {
+        timestamp_column: "WHEN".into(),
+        text_column: "3".into(),
+        timestamp_format: Some("%d/%m/%Y %H:%M".into()),
+        pattern: PATTERN.into(),
+        delimiter: b';',
+        timezone: Timezone::Local,
+    };
+    let parsed = importer.parse(input).unwrap();
+    assert_eq!(
+        summary(&parsed.notes),
+        vec![
+            (local(2025, 3, 7, 9, 0), "standup"),
+            (local(2025, 3, 7, 17, 40), "wrap; up"),
+        ]
+    );
+    assert_eq!(parsed.skipped, vec![(3, "2;bad date;lost".to_string())]);
+
+    let missing = CsvImporter {
+        text_column: "text".into(),
+        ..importer
+    };
+    assert!(missing.parse(input).is_err());
+
+    let defaults = CsvImporter {
+        timestamp_column: "timestamp".into(),
+        text_column: "text".into(),
+        timestamp_format: None,
+        pattern: PATTERN.into(),
+        delimiter: b',',
+        timezone: Timezone::Local,
+    };
+    let parsed = defaults
+        .parse("timestamp,text\n2025-03-07T09:00:00+01:00,standup\n")
+        .unwrap();
+    assert_eq!(
+        parsed.notes[0].timestamp,
+        DateTime::parse_from_rfc3339("2025-03-07T09:00:00+01:00").unwrap()
+    );
+}
+
+#[test]
+fn merge_places_notes_chronologically_and_skips_duplicates() {
+    let store = MemoryStore::with_records(
+        NoteParser::new(PATTERN),
+        vec![
+            "2025-03-07 09:00 standup".into(),
+            "unparsed line".into(),
+            "2025-03-07 17:00 wrap up".into(),
+        ],
+    );
+    let notes = vec![
+        Note::new(local(2025, 3, 8, 8, 0), "next day".into()),
+        Note::new(local(2025, 3, 7, 9, 0), "standup".into()),
+        Note::new(local(2025, 3, 7, 12, 0), "lunch".into()),
+        Note::new(local(2025, 3, 7, 9, 0), "same minute".into()),
+        Note::new(local(2025, 3, 6, 23, 0), "night before".into()),
+        Note::new(local(2025, 3, 7, 12, 0), "lunch".into()),
+    ];
+    let report = merge_notes(&store, notes.clone()).unwrap();
+    assert_eq!(
+        report,
+        MergeReport {
+            added: 4,
+            duplicates: 2,
+        }
+    );
+    assert_eq!(
+        store.read_records().unwrap().unwrap(),
+        vec![
+            "2025-03-06 23:00 night before",
+            "2025-03-07 09:00 standup",
+            "unparsed line",
+            "2025-03-07 09:00 same minute",
+            "2025-03-07 12:00 lunch",
+            "2025-03-07 17:00 wrap up",
+            "2025-03-08 08:00 next day",
+        ]
+    );
+
+    let again = merge_notes(&store, notes).unwrap();
+    assert_eq!(
+        again,
+        MergeReport {
+            added: 0,
+            duplicates: 6,
+        }
+    );
+}
+
+#[test]
+fn reimporting_seconds_precision_notes_into_a_minutes_store_adds_nothing() {
+    let input = "[2025-03-07 09:00:42 AM] Standup\n[2025-03-07 05:40:05 PM] Wrap up\n";
+    let store = MemoryStore::new(NoteParser::new(PATTERN));
+    let importer = JrnlImporter::default();
+    let first = merge_notes(&store, importer.parse(input).unwrap().notes).unwrap();
+    assert_eq!(first.added, 2);
+
+    let again = merge_notes(&store, importer.parse(input).unwrap().notes).unwrap();
+    assert_eq!(
+        again,
+        MergeReport {
+            added: 0,
+            duplicates: 2,
+        }
+    );
+    assert_eq!(
+        store.read_records().unwrap().unwrap(),
+        vec!["2025-03-07 09:00 Standup", "2025-03-07 17:40 Wrap up"]
+    );
+}
@@ -2,14 +2,18 @@ use std::fs;
 use std::io::Cursor;
 use tempfile::TempDir;
 
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
 use nt::interactive::{InteractiveOutcome, run_interactive_session};
-use nt::time::Clock;
+use nt::notes::{Durability, NoteParser};
+use nt::store::{FileStore, MemoryStore, NoteStore};
+use nt::time::{Clock, Timezone};
 
 struct SeqClock {
     times: Vec<String>,
     idx: std::cell::Cell<usize>,
 }
 impl SeqClock {
+    /// Local wall-clock times such as `2025-03-07 09:00`, returned in turn.
     fn new(times: Vec<&str>) -> Self {
         Self {
             times: times.into_iter().map(|s| s.to_string()).collect(),
@@ -18,7 +22,7 @@ impl SeqClock {
     }
 }
 impl Clock for SeqClock {
-    fn now_formatted(&self, _pattern: &str) -> String {
+    fn now(&self) -> DateTime<FixedOffset> {
         let i = self.idx.get();
         let v = if i < self.times.len() {
             &self.times[i]
@@ -26,7 +30,8 @@ impl Clock for SeqClock {
             self.times.last().unwrap()
         };
         self.idx.set(i + 1);
-        v.clone()
+        let naive = NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M").unwrap();
+        Timezone::Local.resolve(naive).unwrap()
     }
 }
 
@@ -44,14 +49,17 @@ fn interactive_single_line_adds_one_note_and_stops() {
     let note_file = tmp_dir.path().join("notes.txt");
     let input = b"single line entry  \nsecond should be ignored\n"; // second line ignored due to single-line mode
     let mut cursor = Cursor::new(&input[..]);
-    let clock = SeqClock::new(vec!["T1"]);
+    let clock = SeqClock::new(vec!["2025-03-07 09:00"]);
     let outcome = run_interactive_session(
         &mut cursor,
         Vec::new(),
         false,
         &clock,
-        "%Y-%m-%d %H:%M",
-        &note_file,
+        &FileStore::new(
+            note_file.clone(),
+            NoteParser::new("%Y-%m-%d %H:%M"),
+            Durability::Fsync,
+        ),
     )
     .unwrap();
     match outcome {
@@ -61,7 +69,20 @@ fn interactive_single_line_adds_one_note_and_stops() {
     let contents = fs::read_to_string(&note_file).unwrap();
     let lines: Vec<&str> = contents.lines().collect();
     assert_eq!(lines.len(), 1);
-    assert_eq!(lines[0], "T1 single line entry  "); // trailing spaces preserved
+    assert_eq!(lines[0], "2025-03-07 09:00 single line entry  "); // trailing spaces preserved
+}
+
+#[test]
+fn interactive_session_appends_to_any_store() {
+    let store = MemoryStore::new(NoteParser::new("%Y-%m-%d %H:%M"));
+    let mut cursor = Cursor::new(&b"into memory\n"[..]);
+    let clock = SeqClock::new(vec!["2025-03-07 09:00"]);
+    let outcome = run_interactive_session(&mut cursor, Vec::new(), false, &clock, &store).unwrap();
+    assert!(matches!(outcome, InteractiveOutcome::Added(1)));
+    assert_eq!(
+        store.read_records().unwrap(),
+        Some(vec!["2025-03-07 09:00 into memory".to_string()])
+    );
 }
 
 #[test]
@@ -70,14 +91,17 @@ fn interactive_blank_line_returns_empty() {
     let note_file = tmp_dir.path().join("notes.txt");
     let input = b"   \nrest ignored"; // whitespace first line => empty
     let mut cursor = Cursor::new(&input[..]);
-    let clock = SeqClock::new(vec!["T1"]);
+    let clock = SeqClock::new(vec!["2025-03-07 09:00"]);
     let outcome = run_interactive_session(
         &mut cursor,
         Vec::new(),
         false,
         &clock,
-        "%Y-%m-%d %H:%M",
-        &note_file,
+        &FileStore::new(
+            note_file.clone(),
+            NoteParser::new("%Y-%m-%d %H:%M"),
+            Durability::Fsync,
+        ),
     )
     .unwrap();
     match outcome {
@@ -96,14 +120,17 @@ fn interactive_immediate_eof_is_empty() {
     let note_file = tmp_dir.path().join("notes.txt");
     let input = b""; // immediate EOF
     let mut cursor = Cursor::new(&input[..]);
-    let clock = SeqClock::new(vec!["T1"]);
+    let clock = SeqClock::new(vec!["2025-03-07 09:00"]);
     let outcome = run_interactive_session(
         &mut cursor,
         Vec::new(),
         false,
         &clock,
-        "%Y-%m-%d %H:%M",
-        &note_file,
+        &FileStore::new(
+            note_file.clone(),
+            NoteParser::new("%Y-%m-%d %H:%M"),
+            Durability::Fsync,
+        ),
     )
     .unwrap();
     match outcome {
@@ -115,3 +142,33 @@ fn interactive_immediate_eof_is_empty() {
         "note file should not be created when empty"
     );
 }
+
+#[test]
+fn interactive_flag_with_piped_stdin_appends_only_first_line() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let tmp_dir = TempDir::new().unwrap();
+    let note_file = tmp_dir.path().join("notes.txt");
+    let (_cfg_handle, cfg_path) = temp_config_file(&note_file);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(&cfg_path)
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn nt");
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        write!(stdin, "typed entry\nnot consumed\n").unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "expected success");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("added 1 note"), "stdout was: {stdout}");
+    let contents = fs::read_to_string(&note_file).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+    assert!(contents.ends_with(" typed entry\n"), "file was: {contents}");
+}
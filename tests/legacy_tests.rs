@@ -0,0 +1,124 @@
+use std::fs;
+use tempfile::TempDir;
+
+use chrono::{Local, TimeZone};
+use nt::import::{NoteImporter, merge_notes};
+use nt::legacy::{
+    LEGACY_DATETIME_FORMAT_PATTERN, LegacyConfig, LegacyImporter, convert_legacy_in_place,
+    parse_legacy_line,
+};
+use nt::notes::{Durability, NoteParser};
+use nt::store::{FileStore, MemoryStore, NoteStore};
+use nt::time::Timezone;
+
+const PATTERN: &str = "%Y-%m-%d %H:%M";
+
+#[test]
+fn legacy_config_parses_like_the_script() {
+    let config = LegacyConfig::parse(
+        "# nt settings\n\n  note_file = \"~/notes/day book.txt\"  \ndatetime_format='%F %R'\nunknown=1\nnot a pair\n",
+    );
+    assert_eq!(
+        config,
+        LegacyConfig {
+            note_file: Some("~/notes/day book.txt".into()),
+            datetime_format: Some("%F %R".into()),
+        }
+    );
+    let empty = LegacyConfig::parse("");
+    assert_eq!(empty.note_file_literal(), "~/daybook.txt");
+    assert_eq!(
+        empty.datetime_format_pattern(),
+        LEGACY_DATETIME_FORMAT_PATTERN
+    );
+}
+
+#[test]
+fn legacy_lines_parse_with_the_default_pattern() {
+    let note = parse_legacy_line(
+        "[9:05 - 3/7/25] standup #team",
+        LEGACY_DATETIME_FORMAT_PATTERN,
+        Timezone::Local,
+    )
+    .unwrap();
+    assert_eq!(
+        note.timestamp,
+        Local.with_ymd_and_hms(2025, 3, 7, 9, 5, 0).unwrap()
+    );
+    assert_eq!(note.text, "standup #team");
+    assert_eq!(note.tags, vec!["team"]);
+    let note = parse_legacy_line(
+        "[14:30 - 12/25/24] ",
+        LEGACY_DATETIME_FORMAT_PATTERN,
+        Timezone::Local,
+    )
+    .unwrap();
+    assert_eq!(note.text, "");
+    assert!(
+        parse_legacy_line(
+            "14:30 - 12/25/24 no brackets",
+            LEGACY_DATETIME_FORMAT_PATTERN,
+            Timezone::Local
+        )
+        .is_none()
+    );
+    assert!(
+        parse_legacy_line(
+            "[yesterday] text",
+            LEGACY_DATETIME_FORMAT_PATTERN,
+            Timezone::Local
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn legacy_importer_reads_notes_and_skips_other_lines() {
+    let importer = LegacyImporter {
+        pattern: LEGACY_DATETIME_FORMAT_PATTERN.to_string(),
+        timezone: Timezone::Local,
+    };
+    let parsed = importer
+        .parse("[9:05 - 3/7/25] standup\nscribble\n\n[17:40 - 3/7/25] wrap up\n")
+        .unwrap();
+    let store = MemoryStore::with_records(
+        NoteParser::new(PATTERN),
+        vec!["2025-03-07 12:00 lunch".into()],
+    );
+    assert_eq!(parsed.skipped, vec![(2, "scribble".to_string())]);
+    merge_notes(&store, parsed.notes).unwrap();
+    assert_eq!(
+        store.read_records().unwrap().unwrap(),
+        vec![
+            "2025-03-07 09:05 standup",
+            "2025-03-07 12:00 lunch",
+            "2025-03-07 17:40 wrap up",
+        ]
+    );
+}
+
+#[test]
+fn in_place_conversion_rewrites_only_legacy_lines() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("daybook.txt");
+    fs::write(
+        &note_file,
+        "[9:05 - 3/7/25] standup\n2025-03-08 10:00 written by the new nt\n",
+    )
+    .unwrap();
+    let store = FileStore::new(
+        note_file.clone(),
+        NoteParser::new(PATTERN),
+        Durability::Flush,
+    );
+
+    let report = convert_legacy_in_place(&store, LEGACY_DATETIME_FORMAT_PATTERN)
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.imported, 1);
+    assert!(report.unparsed.is_empty());
+    assert_eq!(
+        fs::read_to_string(&note_file).unwrap(),
+        "2025-03-07 09:05 standup\n2025-03-08 10:00 written by the new nt\n"
+    );
+}
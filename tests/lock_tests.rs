@@ -0,0 +1,47 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+
+use nt::lock::{NoteLock, lock_file_path};
+use nt::notes::{Durability, append_note_line_to_file};
+
+#[test]
+fn lock_file_sits_next_to_note_file() {
+    assert_eq!(
+        lock_file_path(Path::new("/home/u/notes/daybook.txt")),
+        PathBuf::from("/home/u/notes/daybook.txt.lock")
+    );
+}
+
+#[test]
+fn second_lock_times_out_until_first_is_released() {
+    let tmp = TempDir::new().unwrap();
+    let notes = tmp.path().join("notes.txt");
+    let held = NoteLock::acquire(&notes, Duration::from_secs(1)).unwrap();
+
+    let err = NoteLock::acquire(&notes, Duration::from_millis(50)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert!(err.to_string().contains("notes.txt.lock"), "{err}");
+
+    drop(held);
+    NoteLock::acquire(&notes, Duration::from_millis(50)).unwrap();
+}
+
+#[test]
+fn append_waits_for_the_lock() {
+    let tmp = TempDir::new().unwrap();
+    let notes = tmp.path().join("notes.txt");
+    let held = NoteLock::acquire(&notes, Duration::from_secs(1)).unwrap();
+    let writer = {
+        let notes = notes.clone();
+        std::thread::spawn(move || {
+            append_note_line_to_file(&notes, "12:00", "queued", Durability::Flush)
+        })
+    };
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!notes.exists(), "append ran while the lock was held");
+    drop(held);
+    writer.join().unwrap().unwrap();
+    assert_eq!(std::fs::read_to_string(&notes).unwrap(), "12:00 queued\n");
+}
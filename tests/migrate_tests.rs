@@ -0,0 +1,81 @@
+use std::io;
+
+use chrono::DateTime;
+use nt::migrate::{MigrationReport, migrate_notes};
+use nt::notes::{NoteParser, StorageFormat};
+use nt::store::{MemoryStore, NoteStore};
+use nt::time::FixedClock;
+
+#[test]
+fn migrate_converts_notes_and_copies_unparsed_records() {
+    let source = MemoryStore::new(NoteParser::with_format(
+        "%Y-%m-%d %H:%M",
+        StorageFormat::Jsonl,
+    ));
+    for (ts, text) in [
+        ("2025-03-07T09:00:00+01:00", "standup #team"),
+        ("2025-03-07T16:30:45+01:00", "deploy\nwith notes"),
+    ] {
+        let clock = FixedClock(DateTime::parse_from_rfc3339(ts).unwrap());
+        source.append_note(&clock, text).unwrap();
+    }
+    source.append("{not json").unwrap();
+
+    let target = MemoryStore::new(NoteParser::new("%Y-%m-%dT%H:%M:%S%:z"));
+    let report = migrate_notes(&source, &target).unwrap().unwrap();
+    assert_eq!(
+        report,
+        MigrationReport {
+            migrated: 3,
+            unparsed: vec![(3, "{not json".to_string())],
+            inexact: vec![],
+        }
+    );
+    assert_eq!(
+        target.read_records().unwrap().unwrap(),
+        vec![
+            "2025-03-07T09:00:00+01:00 standup #team",
+            "2025-03-07T16:30:45+01:00 deploy\nwith notes",
+            "{not json",
+        ]
+    );
+    let ids = |store: &MemoryStore| -> Vec<String> {
+        store.entries().unwrap().unwrap()[..2]
+            .iter()
+            .map(|e| e.id())
+            .collect()
+    };
+    assert_eq!(ids(&source), ids(&target));
+}
+
+#[test]
+fn migrate_reports_timestamps_the_target_pattern_drops() {
+    let source = MemoryStore::new(NoteParser::with_format(
+        "%Y-%m-%d %H:%M",
+        StorageFormat::Jsonl,
+    ));
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T16:30:45+00:00").unwrap());
+    source.append_note(&clock, "seconds matter").unwrap();
+
+    let target = MemoryStore::new(NoteParser::new("%Y-%m-%d %H:%M"));
+    let report = migrate_notes(&source, &target).unwrap().unwrap();
+    assert_eq!(report.inexact, vec![1]);
+}
+
+#[test]
+fn migrate_refuses_a_target_with_notes_and_skips_an_empty_source() {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let empty = MemoryStore::new(parser.clone());
+    let target = MemoryStore::new(parser.clone());
+    assert_eq!(migrate_notes(&empty, &target).unwrap(), None);
+    assert!(target.read_records().unwrap().is_none());
+
+    let source = MemoryStore::with_records(parser.clone(), vec!["2025-03-07 09:00 a".into()]);
+    let occupied = MemoryStore::with_records(parser, vec!["2025-03-06 09:00 b".into()]);
+    let err = migrate_notes(&source, &occupied).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(
+        occupied.read_records().unwrap().unwrap(),
+        vec!["2025-03-06 09:00 b"]
+    );
+}
@@ -1,6 +1,13 @@
 use std::io::Cursor;
 
-use nt::notes::{append_note_line_to_writer, collect_last_n_lines_from_reader};
+use chrono::{NaiveDate, Timelike};
+use nt::notes::{
+    Durability, NoteEntry, NoteParser, StorageFormat, append_note_line_to_file,
+    append_note_line_to_writer, collect_last_n_lines_backwards, collect_last_n_lines_from_reader,
+    collect_last_n_notes_from_reader, extract_tags, normalize_note_body, note_id,
+    read_all_notes_from_reader, rewrite_file_atomically, summarize_tags,
+};
+use nt::time::Timezone;
 
 #[test]
 fn append_note_line_to_writer_writes_timestamp_space_text_newline() {
@@ -33,3 +40,380 @@ fn collect_last_n_lines_from_reader_with_fewer_available_returns_existing_lines(
     let lines = collect_last_n_lines_from_reader(cursor, 5).unwrap();
     assert_eq!(lines, vec!["only"]);
 }
+
+#[test]
+fn note_parser_splits_timestamp_and_text_using_pattern() {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let entry = parser.parse_line("2025-03-04 09:15 standup moved  ");
+    let note = entry.as_note().expect("parsed note");
+    assert_eq!(
+        note.timestamp.date_naive(),
+        NaiveDate::from_ymd_opt(2025, 3, 4).unwrap()
+    );
+    assert_eq!((note.timestamp.hour(), note.timestamp.minute()), (9, 15));
+    assert_eq!(note.text, "standup moved  ");
+    assert_eq!(
+        entry.to_line(parser.pattern()),
+        "2025-03-04 09:15 standup moved  "
+    );
+}
+
+#[test]
+fn note_parser_reads_timestamps_without_offset_in_its_timezone() {
+    let tokyo = "Asia/Tokyo".parse::<Timezone>().unwrap();
+    let parser = NoteParser::new("%Y-%m-%d %H:%M").with_timezone(tokyo);
+    let entry = parser.parse_line("2025-03-04 09:15 standup");
+    let note = entry.as_note().expect("parsed note");
+    assert_eq!(note.timestamp.to_rfc3339(), "2025-03-04T09:15:00+09:00");
+
+    let shown = entry.clone().in_timezone(Some(Timezone::Utc));
+    let shown_note = shown.as_note().unwrap();
+    assert_eq!(
+        shown_note.timestamp.to_rfc3339(),
+        "2025-03-04T00:15:00+00:00"
+    );
+    assert_eq!(shown_note.timestamp, note.timestamp);
+    assert_eq!(shown.id(), entry.id());
+    assert_eq!(entry.clone().in_timezone(None), entry);
+}
+
+#[test]
+fn text_records_are_written_in_the_parser_timezone_unless_they_keep_the_offset() {
+    use chrono::DateTime;
+    use nt::time::FixedClock;
+
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T09:00:00+09:00").unwrap());
+    let parser = NoteParser::new("%Y-%m-%d %H:%M").with_timezone(Timezone::Utc);
+    let record = parser.new_record(&clock, "standup");
+    assert_eq!(record, "2025-03-07 00:00 standup");
+    let note = parser.parse_line(&record).as_note().unwrap().clone();
+    assert_eq!(note.timestamp, clock.0);
+    assert_eq!(parser.format_note(&note), record);
+
+    let parser = NoteParser::new("%Y-%m-%d %H:%M %z").with_timezone(Timezone::Utc);
+    assert_eq!(
+        parser.new_record(&clock, "standup"),
+        "2025-03-07 09:00 +0900 standup"
+    );
+}
+
+#[test]
+fn note_parser_keeps_explicit_offset() {
+    let parser = NoteParser::new("%Y-%m-%dT%H:%M%:z");
+    let entry = parser.parse_line("2025-03-04T09:15+05:30 deploy");
+    let note = entry.as_note().expect("parsed note");
+    assert_eq!(
+        note.timestamp.offset().local_minus_utc(),
+        5 * 3600 + 30 * 60
+    );
+    assert_eq!(note.text, "deploy");
+}
+
+#[test]
+fn note_parser_returns_unparsed_for_foreign_lines() {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    assert_eq!(
+        parser.parse_line("T1 not a timestamp"),
+        NoteEntry::Unparsed("T1 not a timestamp".to_string())
+    );
+    assert_eq!(
+        parser.parse_line("2025-03-04 09:155 glued"),
+        NoteEntry::Unparsed("2025-03-04 09:155 glued".to_string())
+    );
+}
+
+#[test]
+fn collect_last_n_notes_from_reader_keeps_unparsed_lines_in_order() {
+    let data = b"2025-01-01 08:00 first\ngarbage\n2025-01-02 08:00 second\n";
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let entries = collect_last_n_notes_from_reader(Cursor::new(data), 2, &parser).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0], NoteEntry::Unparsed("garbage".to_string()));
+    assert_eq!(entries[1].as_note().unwrap().text, "second");
+}
+
+#[test]
+fn extract_tags_only_takes_word_initial_hashes() {
+    assert_eq!(
+        extract_tags(
+            "#Billing fix for (#infra-east), see #42 and C# docs at x.io/#anchor #billing"
+        ),
+        vec!["billing", "infra-east"]
+    );
+    assert_eq!(extract_tags("trailing #ops."), vec!["ops"]);
+    assert!(extract_tags("## heading # alone").is_empty());
+}
+
+#[test]
+fn parsed_notes_carry_their_tags() {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let entry = parser.parse_line("2025-03-04 09:15 paged for #infra");
+    assert_eq!(entry.as_note().unwrap().tags, vec!["infra"]);
+}
+
+#[test]
+fn summarize_tags_counts_usage_and_tracks_last_use() {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let entries: Vec<NoteEntry> = [
+        "2025-03-01 09:00 #infra disk",
+        "2025-03-03 09:00 #billing invoices #infra",
+        "2025-03-02 09:00 #billing refunds",
+        "2025-03-04 09:00 #ops",
+        "not a note #ignored",
+    ]
+    .iter()
+    .map(|l| parser.parse_line(l))
+    .collect();
+    let summaries = summarize_tags(&entries);
+    let rows: Vec<(String, usize, String)> = summaries
+        .iter()
+        .map(|s| {
+            (
+                s.tag.clone(),
+                s.count,
+                s.last_used.format("%Y-%m-%d").to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("billing".to_string(), 2, "2025-03-03".to_string()),
+            ("infra".to_string(), 2, "2025-03-03".to_string()),
+            ("ops".to_string(), 1, "2025-03-04".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn append_note_line_to_writer_indents_continuation_lines() {
+    let mut buf = Vec::new();
+    append_note_line_to_writer(&mut buf, "12:00", "incident\r\n\nroot cause: dns").unwrap();
+    let s = String::from_utf8(buf).unwrap();
+    assert_eq!(s, "12:00 incident\n\t\n\troot cause: dns\n");
+}
+
+#[test]
+fn collect_last_n_lines_from_reader_reassembles_continuations() {
+    let data = b"a\nb one\n\tb two\n\tb three\nc\n";
+    let lines = collect_last_n_lines_from_reader(Cursor::new(data), 2).unwrap();
+    assert_eq!(lines, vec!["b one\nb two\nb three", "c"]);
+}
+
+#[test]
+fn multi_line_note_round_trips_through_parser() {
+    let mut buf = Vec::new();
+    append_note_line_to_writer(
+        &mut buf,
+        "2025-03-04 09:15",
+        "outage #infra\n  step 1\n\nstep 2",
+    )
+    .unwrap();
+    append_note_line_to_writer(&mut buf, "2025-03-04 10:00", "after").unwrap();
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let entries = read_all_notes_from_reader(Cursor::new(&buf), &parser).unwrap();
+    assert_eq!(entries.len(), 2);
+    let note = entries[0].as_note().unwrap();
+    assert_eq!(note.text, "outage #infra\n  step 1\n\nstep 2");
+    assert_eq!(note.tags, vec!["infra"]);
+    let rendered: String = entries
+        .iter()
+        .map(|e| e.to_line(parser.pattern()) + "\n")
+        .collect();
+    assert_eq!(rendered.as_bytes(), &buf[..]);
+}
+
+#[test]
+fn normalize_note_body_trims_surrounding_blank_lines_only() {
+    assert_eq!(
+        normalize_note_body("\n  \nfirst  \r\n\n  indented\n\n"),
+        Some("first  \n\n  indented".to_string())
+    );
+    assert_eq!(normalize_note_body(" \n\t\n"), None);
+}
+
+#[test]
+fn replace_text_keeps_timestamp_exactly_as_written() {
+    let parser = NoteParser::new("%H:%M - %-m/%-d/%y");
+    assert_eq!(
+        parser.replace_text("9:05 - 3/4/25 old text", "new\ntext"),
+        Some("9:05 - 3/4/25 new\ntext".to_string())
+    );
+    assert_eq!(parser.replace_text("garbage", "new"), None);
+}
+
+#[test]
+fn note_ids_are_stable_and_independent_of_offset_and_pattern() {
+    let local = NoteParser::new("%Y-%m-%d %H:%M").parse_line("2025-03-07 11:00 review");
+    let offset = NoteParser::new("%Y-%m-%dT%H:%M%:z").parse_line("2025-03-07T11:00+09:00 review");
+    assert_eq!(local.id(), "cf18223c");
+    assert_eq!(offset.id(), local.id());
+    let note = local.as_note().unwrap();
+    assert_eq!(
+        note_id(&note.timestamp, "review!"),
+        note_id(&note.timestamp, "review!")
+    );
+    assert_ne!(note_id(&note.timestamp, "review!"), note.id);
+}
+
+#[test]
+fn backwards_tail_matches_forward_reader_for_every_block_size() {
+    let inputs: [&[u8]; 7] = [
+        b"",
+        b"a\nb\nc\nd\n",
+        b"a\nb\nc",
+        b"\torphan\n\tmore\na\nb one\n\tb two\n\n\tb after blank\nc\n",
+        b"a\n\nb\n\n",
+        b"x one\n\tx two\n\tx three\n",
+        "ünïcode\n\tçontinued\nlast ✓\n".as_bytes(),
+    ];
+    for data in inputs {
+        for count in 0..6 {
+            let expected = collect_last_n_lines_from_reader(Cursor::new(data), count).unwrap();
+            for block_size in [1, 2, 3, 5, 8, 64] {
+                let actual =
+                    collect_last_n_lines_backwards(Cursor::new(data), count, block_size).unwrap();
+                assert_eq!(
+                    actual,
+                    expected,
+                    "data={:?} count={count} block_size={block_size}",
+                    String::from_utf8_lossy(data)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn backwards_tail_stops_reading_once_enough_records_are_found() {
+    struct CountingCursor {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+    impl std::io::Read for CountingCursor {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.bytes_read += n;
+            Ok(n)
+        }
+    }
+    impl std::io::Seek for CountingCursor {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    let mut data = Vec::new();
+    for i in 0..10_000 {
+        append_note_line_to_writer(&mut data, "2025-01-01 00:00", &format!("note {i}")).unwrap();
+    }
+    let mut reader = CountingCursor {
+        inner: Cursor::new(data),
+        bytes_read: 0,
+    };
+    let lines = collect_last_n_lines_backwards(&mut reader, 3, 256).unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            "2025-01-01 00:00 note 9997",
+            "2025-01-01 00:00 note 9998",
+            "2025-01-01 00:00 note 9999"
+        ]
+    );
+    assert!(reader.bytes_read <= 256, "read {} bytes", reader.bytes_read);
+}
+
+#[test]
+fn append_creates_missing_directories_under_every_durability() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let path = tmp.path().join("log").join("notes.txt");
+    for (durability, text) in [
+        (Durability::None, "none"),
+        (Durability::Flush, "flush"),
+        (Durability::Fsync, "fsync"),
+    ] {
+        append_note_line_to_file(&path, "12:00", text, durability).unwrap();
+    }
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "12:00 none\n12:00 flush\n12:00 fsync\n"
+    );
+}
+
+#[test]
+fn atomic_rewrite_replaces_contents_and_keeps_original_on_failure() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = tempfile::TempDir::new().unwrap();
+    let path = tmp.path().join("notes.txt");
+    std::fs::write(&path, "old\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    let err = rewrite_file_atomically(&path, |w| {
+        w.write_all(b"half")?;
+        Err(std::io::Error::other("disk full"))
+    })
+    .unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "old\n");
+
+    rewrite_file_atomically(&path, |w| w.write_all(b"new\n")).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let leftovers: Vec<_> = std::fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(leftovers, vec![std::ffi::OsString::from("notes.txt")]);
+}
+
+#[test]
+fn jsonl_records_round_trip_with_exact_offsets() {
+    use chrono::DateTime;
+    use nt::time::FixedClock;
+
+    let parser = NoteParser::with_format("%Y-%m-%d %H:%M", StorageFormat::Jsonl);
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T11:00:42+09:00").unwrap());
+    let record = parser.new_record(&clock, "review \"ops\" #Infra\nsecond line");
+    assert_eq!(
+        record,
+        r#"{"ts":"2025-03-07T11:00:42+09:00","text":"review \"ops\" #Infra\nsecond line","tags":["infra"],"id":"6c392f9f"}"#
+    );
+
+    let entry = parser.parse_line(&record);
+    let note = entry.as_note().expect("parsed note");
+    assert_eq!(note.timestamp, clock.0);
+    assert_eq!(note.text, "review \"ops\" #Infra\nsecond line");
+    assert_eq!(note.tags, vec!["infra"]);
+    assert_eq!(entry.id(), "6c392f9f");
+    assert_eq!(
+        entry.to_line(parser.pattern()),
+        "2025-03-07 11:00 review \"ops\" #Infra\n\tsecond line"
+    );
+
+    let replaced = parser.replace_text(&record, "done #ops").unwrap();
+    assert_eq!(
+        replaced,
+        format!(
+            r#"{{"ts":"2025-03-07T11:00:42+09:00","text":"done #ops","tags":["ops"],"id":"{}"}}"#,
+            note_id(&clock.0, "done #ops")
+        )
+    );
+}
+
+#[test]
+fn jsonl_parser_keeps_foreign_lines_unparsed() {
+    let parser = NoteParser::with_format("%Y-%m-%d %H:%M", StorageFormat::Jsonl);
+    for line in [
+        "2025-03-07 11:00 plain text note",
+        r#"{"ts":"yesterday","text":"bad ts"}"#,
+        r#"{"text":"no ts"}"#,
+    ] {
+        assert_eq!(
+            parser.parse_line(line),
+            NoteEntry::Unparsed(line.to_string())
+        );
+    }
+}
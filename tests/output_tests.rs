@@ -0,0 +1,88 @@
+use chrono::DateTime;
+use nt::notes::{Note, NoteEntry};
+use nt::output::{NoteOutput, OutputArgs, OutputTemplate, TemplateError};
+
+const PATTERN: &str = "%Y-%m-%d %H:%M";
+
+fn note() -> NoteEntry {
+    NoteEntry::Parsed(Note::new(
+        DateTime::parse_from_rfc3339("2025-03-07T09:00:00+01:00").unwrap(),
+        "standup #team {x}".into(),
+    ))
+}
+
+#[test]
+fn template_fills_fields_and_unescapes() {
+    let template = OutputTemplate::parse(r"{ts}\t{text}").unwrap();
+    assert_eq!(
+        template.render(&note(), PATTERN),
+        "2025-03-07T09:00:00+01:00\tstandup #team {x}"
+    );
+    let template = OutputTemplate::parse(r"[{time}] {{{id}}} {tags}\\n").unwrap();
+    assert_eq!(
+        template.render(&note(), PATTERN),
+        format!("[2025-03-07 09:00] {{{}}} #team\\n", note().id())
+    );
+    let raw = NoteEntry::Unparsed("stray".into());
+    assert_eq!(
+        OutputTemplate::parse("<{ts}|{time}|{text}>")
+            .unwrap()
+            .render(&raw, PATTERN),
+        "<||stray>"
+    );
+}
+
+#[test]
+fn template_rejects_unknown_fields_and_stray_braces() {
+    assert_eq!(
+        OutputTemplate::parse("{date}"),
+        Err(TemplateError::UnknownField("date".into()))
+    );
+    assert_eq!(
+        OutputTemplate::parse("{text"),
+        Err(TemplateError::UnmatchedBrace)
+    );
+    assert_eq!(
+        OutputTemplate::parse("text}"),
+        Err(TemplateError::UnmatchedBrace)
+    );
+}
+
+#[test]
+fn json_output_is_one_object_per_line() {
+    let args = OutputArgs {
+        json: true,
+        ..OutputArgs::default()
+    };
+    let output = args.note_output().unwrap().unwrap();
+    assert_eq!(output, NoteOutput::Json);
+    let line = output.render(&note(), PATTERN);
+    assert!(!line.contains('\n'));
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["ts"], "2025-03-07T09:00:00+01:00");
+    assert_eq!(value["text"], "standup #team {x}");
+    assert_eq!(value["tags"], serde_json::json!(["team"]));
+    assert!(OutputArgs::default().note_output().unwrap().is_none());
+}
+
+#[test]
+fn template_escapes_multi_line_notes_onto_one_line() {
+    let entry = NoteEntry::Parsed(Note::new(
+        DateTime::parse_from_rfc3339("2025-03-07T09:00:00+01:00").unwrap(),
+        "deploy\n\tstep 1 C:\\temp".into(),
+    ));
+    let rendered = OutputTemplate::parse(r"{ts}\t{text}")
+        .unwrap()
+        .render(&entry, PATTERN);
+    assert_eq!(
+        rendered,
+        "2025-03-07T09:00:00+01:00\tdeploy\\n\\tstep 1 C:\\\\temp"
+    );
+    assert_eq!(rendered.lines().count(), 1);
+    assert_eq!(
+        OutputTemplate::parse("{time}")
+            .unwrap()
+            .render(&entry, "%Y-%m-%d\n%H:%M"),
+        r"2025-03-07\n09:00"
+    );
+}
@@ -0,0 +1,128 @@
+use nt::notes::{NoteEntry, NoteParser};
+use nt::query::{FilterArgs, NoteFilter};
+use nt::store::{MemoryStore, NoteStore};
+use nt::time::Timezone;
+
+const PATTERN: &str = "%Y-%m-%d %H:%M";
+
+/// The last `count` of `lines` that pass `filter`, as the store selects them.
+fn select_last_n(lines: &[&str], filter: &NoteFilter, count: usize) -> Vec<NoteEntry> {
+    let records = lines.iter().map(|l| l.to_string()).collect();
+    MemoryStore::with_records(NoteParser::new(PATTERN), records)
+        .last_entries(count, filter)
+        .unwrap()
+        .unwrap()
+}
+
+fn texts(entries: &[NoteEntry]) -> Vec<String> {
+    entries.iter().map(|e| e.to_line(PATTERN)).collect()
+}
+
+fn range(since: Option<&str>, until: Option<&str>, on: Option<&str>) -> FilterArgs {
+    FilterArgs {
+        since: since.map(str::to_string),
+        until: until.map(str::to_string),
+        on: on.map(str::to_string),
+        ..FilterArgs::default()
+    }
+}
+
+fn sample() -> &'static [&'static str] {
+    &[
+        "2025-03-06 17:00 thursday wrap",
+        "2025-03-07 09:00 friday standup",
+        "not a note",
+        "2025-03-07 16:30 friday deploy",
+        "2025-03-08 10:00 saturday",
+    ]
+}
+
+#[test]
+fn on_selects_whole_day_and_drops_unparsed_lines() {
+    let filter = NoteFilter::from_filter_args(
+        &range(None, None, Some("2025-03-07")),
+        PATTERN,
+        &Timezone::Local,
+    )
+    .unwrap();
+    let selected = select_last_n(sample(), &filter, 10);
+    assert_eq!(
+        texts(&selected),
+        vec![
+            "2025-03-07 09:00 friday standup",
+            "2025-03-07 16:30 friday deploy"
+        ]
+    );
+}
+
+#[test]
+fn since_accepts_configured_pattern_and_is_inclusive() {
+    let filter = NoteFilter::from_filter_args(
+        &range(Some("2025-03-07 09:00"), None, None),
+        PATTERN,
+        &Timezone::Local,
+    )
+    .unwrap();
+    let selected = select_last_n(sample(), &filter, 10);
+    assert_eq!(selected.len(), 3);
+    assert_eq!(
+        texts(&selected)[0],
+        "2025-03-07 09:00 friday standup".to_string()
+    );
+}
+
+#[test]
+fn until_date_includes_whole_day_and_count_keeps_latest() {
+    let filter = NoteFilter::from_filter_args(
+        &range(Some("2025-03-06T12:00:00"), Some("2025-03-07"), None),
+        PATTERN,
+        &Timezone::Local,
+    )
+    .unwrap();
+    let selected = select_last_n(sample(), &filter, 2);
+    assert_eq!(
+        texts(&selected),
+        vec![
+            "2025-03-07 09:00 friday standup",
+            "2025-03-07 16:30 friday deploy"
+        ]
+    );
+}
+
+#[test]
+fn unbounded_filter_keeps_unparsed_lines() {
+    let filter =
+        NoteFilter::from_filter_args(&FilterArgs::default(), PATTERN, &Timezone::Local).unwrap();
+    assert!(filter.is_unbounded());
+    assert_eq!(select_last_n(sample(), &filter, 10).len(), 5);
+}
+
+#[test]
+fn invalid_bound_is_reported() {
+    let err = NoteFilter::from_filter_args(
+        &range(Some("last blue moon"), None, None),
+        PATTERN,
+        &Timezone::Local,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("last blue moon"));
+}
+
+#[test]
+fn tag_filter_requires_every_requested_tag() {
+    let notes = [
+        "2025-03-07 09:00 rotated keys #infra #Security",
+        "2025-03-07 10:00 invoice run #billing",
+        "2025-03-07 11:00 disk alert #infra",
+    ];
+    let args = FilterArgs {
+        tags: vec!["#INFRA".to_string(), "security".to_string()],
+        ..FilterArgs::default()
+    };
+    let filter = NoteFilter::from_filter_args(&args, PATTERN, &Timezone::Local).unwrap();
+    assert!(!filter.is_unbounded());
+    assert_eq!(
+        texts(&select_last_n(&notes, &filter, 10)),
+        vec!["2025-03-07 09:00 rotated keys #infra #Security"]
+    );
+}
@@ -0,0 +1,108 @@
+use nt::notes::NoteParser;
+use nt::search::{NoteMatcher, SearchMode};
+
+fn matches(pattern: &str, mode: SearchMode, line: &str) -> bool {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    NoteMatcher::new(pattern, mode)
+        .unwrap()
+        .is_match(&parser.parse_line(line))
+}
+
+#[test]
+fn substring_mode_ignores_case() {
+    assert!(matches(
+        "deploy",
+        SearchMode::Substring,
+        "2025-03-07 09:00 Deployed api"
+    ));
+    assert!(!matches(
+        "rollback",
+        SearchMode::Substring,
+        "2025-03-07 09:00 Deployed api"
+    ));
+}
+
+#[test]
+fn substring_mode_treats_pattern_literally() {
+    assert!(matches(
+        "v1.2",
+        SearchMode::Substring,
+        "2025-03-07 09:00 shipped v1.2"
+    ));
+    assert!(!matches(
+        "v1.2",
+        SearchMode::Substring,
+        "2025-03-07 09:00 shipped v132"
+    ));
+}
+
+#[test]
+fn substring_mode_does_not_match_timestamp() {
+    assert!(!matches(
+        "2025",
+        SearchMode::Substring,
+        "2025-03-07 09:00 lunch"
+    ));
+}
+
+#[test]
+fn whole_word_mode_requires_word_boundaries() {
+    assert!(matches(
+        "api",
+        SearchMode::WholeWord,
+        "2025-03-07 09:00 restarted API server"
+    ));
+    assert!(!matches(
+        "api",
+        SearchMode::WholeWord,
+        "2025-03-07 09:00 rapid fix"
+    ));
+}
+
+#[test]
+fn whole_word_mode_matches_words_that_start_or_end_with_symbols() {
+    assert!(matches(
+        "#infra",
+        SearchMode::WholeWord,
+        "2025-03-07 09:00 deployed #infra"
+    ));
+    assert!(!matches(
+        "#infra",
+        SearchMode::WholeWord,
+        "2025-03-07 09:00 deployed #infrastructure"
+    ));
+    assert!(matches(
+        "c++",
+        SearchMode::WholeWord,
+        "2025-03-07 09:00 c++ build fixed"
+    ));
+    assert!(!matches(
+        "c++",
+        SearchMode::WholeWord,
+        "2025-03-07 09:00 objc++ build fixed"
+    ));
+}
+
+#[test]
+fn regex_mode_is_case_sensitive_by_default() {
+    assert!(matches(
+        r"PR-\d+",
+        SearchMode::Regex,
+        "2025-03-07 09:00 merged PR-42"
+    ));
+    assert!(!matches(
+        r"pr-\d+",
+        SearchMode::Regex,
+        "2025-03-07 09:00 merged PR-42"
+    ));
+    assert!(NoteMatcher::new("(", SearchMode::Regex).is_err());
+}
+
+#[test]
+fn unparsed_lines_match_on_raw_text() {
+    assert!(matches(
+        "legacy",
+        SearchMode::Substring,
+        "[09:00] legacy entry"
+    ));
+}
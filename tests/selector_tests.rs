@@ -0,0 +1,49 @@
+use nt::notes::{NoteEntry, NoteParser};
+use nt::selector::{NoteSelector, SelectorError};
+
+fn entries() -> Vec<NoteEntry> {
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    [
+        "2025-03-06 17:00 deploy prep",
+        "2025-03-07 09:00 standup",
+        "2025-03-07 16:30 deployed api",
+        "2025-03-08 10:00 weekend",
+    ]
+    .iter()
+    .map(|l| parser.parse_line(l))
+    .collect()
+}
+
+fn resolve(selector: &str) -> Result<usize, SelectorError> {
+    selector.parse::<NoteSelector>()?.resolve(&entries())
+}
+
+#[test]
+fn index_selector_counts_back_from_most_recent() {
+    assert_eq!(resolve("@1"), Ok(3));
+    assert_eq!(resolve("@3"), Ok(1));
+    assert!(matches!(resolve("@5"), Err(SelectorError::NotFound(_))));
+    assert!(matches!(resolve("@0"), Err(SelectorError::Invalid(_))));
+}
+
+#[test]
+fn id_selector_matches_unique_prefix() {
+    let all = entries();
+    let id = all[1].id();
+    assert_eq!(resolve(&format!("#{}", &id[..4])), Ok(1));
+    assert_eq!(resolve(&format!("#{}", id.to_uppercase())), Ok(1));
+    assert!(matches!(resolve("#abc"), Err(SelectorError::Invalid(_))));
+    assert!(matches!(resolve("#xyz1"), Err(SelectorError::Invalid(_))));
+}
+
+#[test]
+fn regex_selector_picks_most_recent_match() {
+    assert_eq!(resolve("/deploy/"), Ok(2));
+    assert_eq!(resolve("/^deploy prep$/"), Ok(0));
+    assert!(matches!(
+        resolve("/nothing/"),
+        Err(SelectorError::NotFound(_))
+    ));
+    assert!(matches!(resolve("/(/"), Err(SelectorError::Invalid(_))));
+    assert!(matches!(resolve("plain"), Err(SelectorError::Invalid(_))));
+}
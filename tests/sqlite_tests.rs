@@ -0,0 +1,257 @@
+#![cfg(feature = "sqlite")]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+use chrono::DateTime;
+use nt::notes::{Durability, NoteParser, StorageFormat};
+use nt::query::{FilterArgs, NoteFilter};
+use nt::search::{NoteMatcher, SearchMode};
+use nt::sqlite::SqliteStore;
+use nt::store::NoteStore;
+use nt::time::{FixedClock, Timezone};
+
+const PATTERN: &str = "%Y-%m-%d %H:%M";
+
+fn store(dir: &Path) -> SqliteStore {
+    SqliteStore::new(
+        dir.join("notes.db"),
+        NoteParser::with_format(PATTERN, StorageFormat::Sqlite),
+        Durability::Flush,
+    )
+}
+
+fn texts(entries: Option<Vec<nt::notes::NoteEntry>>) -> Vec<String> {
+    entries
+        .unwrap()
+        .iter()
+        .map(|e| e.as_note().unwrap().text.clone())
+        .collect()
+}
+
+#[test]
+fn database_is_created_on_first_append_and_records_are_json() {
+    let tmp = TempDir::new().unwrap();
+    let store = store(tmp.path());
+    assert!(store.read_records().unwrap().is_none());
+    assert!(
+        !store.path().exists(),
+        "reading must not create the database"
+    );
+
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T11:00:00+01:00").unwrap());
+    store
+        .append_note(&clock, "review #ops\nsecond line")
+        .unwrap();
+    assert_eq!(
+        store.read_records().unwrap(),
+        Some(vec![
+            r#"{"ts":"2025-03-07T11:00:00+01:00","text":"review #ops\nsecond line","tags":["ops"],"id":"545637d7"}"#
+                .to_string()
+        ])
+    );
+}
+
+#[test]
+fn filters_and_searches_use_the_indexes_with_exact_results() {
+    let tmp = TempDir::new().unwrap();
+    let store = store(tmp.path());
+    let parser = store.parser().clone();
+    let notes = [
+        ("2025-03-06T17:00:00Z", "wrap up #infra"),
+        ("2025-03-07T09:00:00Z", "Standup, deploy plan"),
+        ("2025-03-07T16:30:00Z", "deployed #infra #ops"),
+        ("2025-03-08T10:00:00Z", "saturday"),
+    ];
+    for (ts, text) in notes {
+        store
+            .append_note(&FixedClock(DateTime::parse_from_rfc3339(ts).unwrap()), text)
+            .unwrap();
+    }
+    store.append("not json").unwrap();
+
+    let filter =
+        |args: FilterArgs| NoteFilter::from_filter_args(&args, PATTERN, &Timezone::Local).unwrap();
+    let all = NoteFilter::default();
+    let infra = filter(FilterArgs {
+        tags: vec!["infra".into()],
+        ..FilterArgs::default()
+    });
+    let on_7th = filter(FilterArgs {
+        since: Some("2025-03-07T00:00:00Z".into()),
+        until: Some("2025-03-07T23:59:59Z".into()),
+        ..FilterArgs::default()
+    });
+
+    assert_eq!(store.entries().unwrap().unwrap().len(), 5);
+    assert_eq!(
+        texts(store.find_entries(&infra, None).unwrap()),
+        vec!["wrap up #infra", "deployed #infra #ops"]
+    );
+    assert_eq!(
+        texts(store.find_entries(&on_7th, None).unwrap()),
+        vec!["Standup, deploy plan", "deployed #infra #ops"]
+    );
+
+    let substring = NoteMatcher::new("DEPLOY", SearchMode::Substring).unwrap();
+    assert_eq!(
+        texts(store.find_entries(&on_7th, Some(&substring)).unwrap()),
+        vec!["Standup, deploy plan", "deployed #infra #ops"]
+    );
+    let word = NoteMatcher::new("deploy", SearchMode::WholeWord).unwrap();
+    assert_eq!(
+        texts(store.find_entries(&all, Some(&word)).unwrap()),
+        vec!["Standup, deploy plan"]
+    );
+    let short = NoteMatcher::new("up", SearchMode::Substring).unwrap();
+    assert_eq!(
+        texts(store.find_entries(&infra, Some(&short)).unwrap()),
+        vec!["wrap up #infra"]
+    );
+    let regex = NoteMatcher::new("^not", SearchMode::Regex).unwrap();
+    assert_eq!(
+        store
+            .find_entries(&all, Some(&regex))
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let last = store.last_entries(2, &infra).unwrap().unwrap();
+    assert_eq!(
+        last.iter().map(|e| e.to_line(PATTERN)).collect::<Vec<_>>(),
+        vec![
+            "2025-03-06 17:00 wrap up #infra".to_string(),
+            "2025-03-07 16:30 deployed #infra #ops".to_string()
+        ]
+    );
+    let newest: Vec<String> = store
+        .records_rev()
+        .unwrap()
+        .unwrap()
+        .take(1)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(newest, vec!["not json".to_string()]);
+    assert!(matches!(
+        parser.parse_line(&newest[0]),
+        nt::notes::NoteEntry::Unparsed(_)
+    ));
+}
+
+#[test]
+fn dropping_the_lock_without_releasing_it_discards_its_writes() {
+    let tmp = TempDir::new().unwrap();
+    let store = store(tmp.path());
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T09:00:00+01:00").unwrap());
+    store.append_note(&clock, "kept").unwrap();
+
+    let lock = store.lock().unwrap();
+    store.rewrite(&[]).unwrap();
+    drop(lock);
+
+    assert_eq!(texts(store.entries().unwrap()), vec!["kept"]);
+}
+
+#[test]
+fn rewrite_under_the_lock_replaces_rows_and_their_index_entries() {
+    let tmp = TempDir::new().unwrap();
+    let store = store(tmp.path());
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T09:00:00+01:00").unwrap());
+    store.append_note(&clock, "first #a").unwrap();
+    store.append_note(&clock, "second #b").unwrap();
+
+    let lock = store.lock().unwrap();
+    let mut all = store.read_records().unwrap().unwrap();
+    all[0] = store.parser().replace_text(&all[0], "edited #c").unwrap();
+    all.pop();
+    store.rewrite(&all).unwrap();
+    store.append_note(&clock, "appended while locked").unwrap();
+    lock.release().unwrap();
+
+    assert_eq!(
+        texts(store.entries().unwrap()),
+        vec!["edited #c", "appended while locked"]
+    );
+    let tagged = |tag: &str| {
+        NoteFilter::from_filter_args(
+            &FilterArgs {
+                tags: vec![tag.into()],
+                ..FilterArgs::default()
+            },
+            PATTERN,
+            &Timezone::Local,
+        )
+        .unwrap()
+    };
+    assert!(texts(store.find_entries(&tagged("a"), None).unwrap()).is_empty());
+    assert_eq!(
+        texts(store.find_entries(&tagged("c"), None).unwrap()),
+        vec!["edited #c"]
+    );
+    let second = NoteMatcher::new("second", SearchMode::Substring).unwrap();
+    assert!(
+        texts(
+            store
+                .find_entries(&NoteFilter::default(), Some(&second))
+                .unwrap()
+        )
+        .is_empty()
+    );
+}
+
+fn nt(config: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nt"))
+        .arg("--config-file")
+        .arg(config)
+        .args(args)
+        .output()
+        .expect("run nt")
+}
+
+#[test]
+fn sqlite_format_is_selected_from_the_config() {
+    let tmp = TempDir::new().unwrap();
+    let note_file = tmp.path().join("notes.db");
+    let config = tmp.path().join("cfg.toml");
+    fs::write(
+        &config,
+        format!(
+            "note_file = \"{}\"\nformat = \"sqlite\"\n",
+            note_file.display()
+        ),
+    )
+    .unwrap();
+
+    for (at, text) in [
+        ("2025-03-07T11:00:00+01:00", "review #ops"),
+        ("2025-03-07T16:30:00+01:00", "deploy"),
+    ] {
+        assert!(nt(&config, &["--at", at, text]).status.success());
+    }
+    assert!(
+        fs::read(&note_file)
+            .unwrap()
+            .starts_with(b"SQLite format 3\0")
+    );
+
+    let print = nt(&config, &["-p"]);
+    assert_eq!(
+        String::from_utf8(print.stdout).unwrap(),
+        "c21cc115 2025-03-07 11:00 review #ops\nc12750aa 2025-03-07 16:30 deploy\n"
+    );
+    let search = nt(&config, &["search", "REVIEW"]);
+    assert_eq!(
+        String::from_utf8(search.stdout).unwrap(),
+        "2025-03-07 11:00 review #ops\n"
+    );
+    assert!(nt(&config, &["rm", "-y", "#c12750aa"]).status.success());
+    let print = nt(&config, &["-p"]);
+    assert_eq!(
+        String::from_utf8(print.stdout).unwrap(),
+        "c21cc115 2025-03-07 11:00 review #ops\n"
+    );
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use tempfile::TempDir;
+
+use chrono::DateTime;
+use nt::notes::{Durability, NoteParser, StorageFormat};
+use nt::query::{FilterArgs, NoteFilter};
+use nt::store::{FileStore, MemoryStore, NoteStore};
+use nt::time::{FixedClock, Timezone};
+
+const PATTERN: &str = "%Y-%m-%d %H:%M";
+
+fn records(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|l| l.to_string()).collect()
+}
+
+fn lines(store: &dyn NoteStore, count: usize, filter: &NoteFilter) -> Vec<String> {
+    store
+        .last_entries(count, filter)
+        .unwrap()
+        .unwrap()
+        .iter()
+        .map(|e| e.to_line(PATTERN))
+        .collect()
+}
+
+#[test]
+fn stores_are_missing_until_first_append() {
+    let tmp = TempDir::new().unwrap();
+    let parser = NoteParser::new(PATTERN).with_timezone(Timezone::Utc);
+    let file = FileStore::new(
+        tmp.path().join("notes.txt"),
+        parser.clone(),
+        Durability::Flush,
+    );
+    let memory = MemoryStore::new(parser);
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T09:00:00Z").unwrap());
+    for store in [&file as &dyn NoteStore, &memory] {
+        assert!(store.read_records().unwrap().is_none());
+        assert!(store.entries().unwrap().is_none());
+        assert!(
+            store
+                .last_entries(5, &NoteFilter::default())
+                .unwrap()
+                .is_none()
+        );
+        store.append_note(&clock, "standup\nnotes").unwrap();
+        assert_eq!(
+            store.read_records().unwrap(),
+            Some(records(&["2025-03-07 09:00 standup\nnotes"]))
+        );
+    }
+    assert_eq!(
+        fs::read_to_string(file.path()).unwrap(),
+        "2025-03-07 09:00 standup\n\tnotes\n"
+    );
+}
+
+#[test]
+fn last_entries_reads_newest_first_and_applies_filter() {
+    let tmp = TempDir::new().unwrap();
+    let contents = [
+        "2025-03-06 17:00 wrap #infra",
+        "2025-03-07 09:00 standup",
+        "garbage",
+        "2025-03-07 16:30 deploy #infra",
+        "2025-03-08 10:00 saturday",
+    ];
+    let path = tmp.path().join("notes.txt");
+    fs::write(&path, contents.join("\n") + "\n").unwrap();
+    let file = FileStore::new(path, NoteParser::new(PATTERN), Durability::Flush);
+    let memory = MemoryStore::with_records(NoteParser::new(PATTERN), records(&contents));
+
+    let tagged = NoteFilter::from_filter_args(
+        &FilterArgs {
+            tags: vec!["infra".into()],
+            ..FilterArgs::default()
+        },
+        PATTERN,
+        &Timezone::Local,
+    )
+    .unwrap();
+    for store in [&file as &dyn NoteStore, &memory] {
+        assert_eq!(
+            lines(store, 3, &NoteFilter::default()),
+            records(&contents[2..])
+        );
+        assert_eq!(lines(store, 1, &tagged), records(&[contents[3]]));
+        assert_eq!(
+            lines(store, 10, &tagged),
+            records(&[contents[0], contents[3]])
+        );
+        let reversed: Vec<String> = store
+            .records_rev()
+            .unwrap()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            reversed,
+            records(&contents).into_iter().rev().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn rewrite_replaces_all_records_under_the_lock() {
+    let tmp = TempDir::new().unwrap();
+    let parser = NoteParser::with_format(PATTERN, StorageFormat::Jsonl);
+    let file = FileStore::new(
+        tmp.path().join("notes.jsonl"),
+        parser.clone(),
+        Durability::Fsync,
+    );
+    let memory = MemoryStore::new(parser);
+    let clock = FixedClock(DateTime::parse_from_rfc3339("2025-03-07T09:00:00+01:00").unwrap());
+    for store in [&file as &dyn NoteStore, &memory] {
+        store.append_note(&clock, "first").unwrap();
+        store.append_note(&clock, "second").unwrap();
+        let lock = store.lock().unwrap();
+        let mut all = store.read_records().unwrap().unwrap();
+        all[0] = store.parser().replace_text(&all[0], "edited").unwrap();
+        all.pop();
+        store.rewrite(&all).unwrap();
+        lock.release().unwrap();
+        let texts: Vec<String> = store
+            .entries()
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|e| e.as_note().unwrap().text.clone())
+            .collect();
+        assert_eq!(texts, vec!["edited"]);
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use nt::time::{
+    Clock, FixedClock, TimePoint, Timezone, UnknownTimezone, parse_relative_time_point,
+    parse_time_point,
+};
+
+// Wednesday
+fn clock() -> FixedClock {
+    FixedClock(DateTime::parse_from_rfc3339("2025-03-12T15:45:00+00:00").unwrap())
+}
+
+fn day(y: i32, m: u32, d: u32) -> TimePoint {
+    TimePoint::Day(NaiveDate::from_ymd_opt(y, m, d).unwrap())
+}
+
+fn local(point: TimePoint) -> NaiveDateTime {
+    match point {
+        TimePoint::Instant(dt) => dt.naive_local(),
+        TimePoint::Day(d) => panic!("expected instant, got day {d}"),
+    }
+}
+
+#[test]
+fn resolves_named_days() {
+    let c = clock();
+    assert_eq!(
+        parse_relative_time_point("today", &c),
+        Some(day(2025, 3, 12))
+    );
+    assert_eq!(
+        parse_relative_time_point("Yesterday", &c),
+        Some(day(2025, 3, 11))
+    );
+    assert_eq!(
+        parse_relative_time_point("tomorrow", &c),
+        Some(day(2025, 3, 13))
+    );
+}
+
+#[test]
+fn resolves_weekdays() {
+    let c = clock();
+    assert_eq!(
+        parse_relative_time_point("last monday", &c),
+        Some(day(2025, 3, 10))
+    );
+    assert_eq!(
+        parse_relative_time_point("last wed", &c),
+        Some(day(2025, 3, 5))
+    );
+    assert_eq!(
+        parse_relative_time_point("wednesday", &c),
+        Some(day(2025, 3, 12))
+    );
+    assert_eq!(
+        parse_relative_time_point("friday", &c),
+        Some(day(2025, 3, 7))
+    );
+}
+
+#[test]
+fn resolves_durations_ago() {
+    let c = clock();
+    let expected = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+    let ago = |s: &str| local(parse_relative_time_point(s, &c).unwrap());
+    assert_eq!(ago("2h ago"), expected("2025-03-12 13:45"));
+    assert_eq!(ago("30m ago"), expected("2025-03-12 15:15"));
+    assert_eq!(ago("3 days ago"), expected("2025-03-09 15:45"));
+    assert_eq!(ago("1 week ago"), expected("2025-03-05 15:45"));
+    assert_eq!(parse_relative_time_point("ago", &c), None);
+    assert_eq!(parse_relative_time_point("2 fortnights ago", &c), None);
+}
+
+#[test]
+fn resolves_day_with_time_of_day() {
+    let c = clock();
+    let at = |s: &str| {
+        local(parse_relative_time_point(s, &c).unwrap())
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    assert_eq!(at("today 14:00"), "2025-03-12 14:00");
+    assert_eq!(at("yesterday 9am"), "2025-03-11 09:00");
+    assert_eq!(at("last friday 5:30pm"), "2025-03-07 17:30");
+    assert_eq!(parse_relative_time_point("today 25:00", &c), None);
+}
+
+#[test]
+fn absolute_forms_take_precedence() {
+    let c = clock();
+    assert_eq!(
+        parse_time_point("2025-01-02", "%Y-%m-%d %H:%M", &c),
+        Some(day(2025, 1, 2))
+    );
+    assert_eq!(parse_time_point("someday", "%Y-%m-%d %H:%M", &c), None);
+}
+
+#[test]
+fn timezones_parse_resolve_and_convert() {
+    let tokyo: Timezone = "Asia/Tokyo".parse().unwrap();
+    assert_eq!(tokyo.to_string(), "Asia/Tokyo");
+    assert_eq!("utc".parse::<Timezone>().unwrap(), Timezone::Utc);
+    assert_eq!("Local".parse::<Timezone>().unwrap(), Timezone::Local);
+    assert_eq!(
+        "Mars/Olympus".parse::<Timezone>(),
+        Err(UnknownTimezone("Mars/Olympus".into()))
+    );
+
+    let naive = NaiveDate::from_ymd_opt(2025, 3, 7)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let in_tokyo = tokyo.resolve(naive).unwrap();
+    assert_eq!(in_tokyo.to_rfc3339(), "2025-03-07T09:00:00+09:00");
+    assert_eq!(
+        Timezone::Utc.convert(in_tokyo).to_rfc3339(),
+        "2025-03-07T00:00:00+00:00"
+    );
+    // 02:30 does not exist in New York on the day clocks go forward.
+    let new_york: Timezone = "America/New_York".parse().unwrap();
+    let skipped = NaiveDate::from_ymd_opt(2025, 3, 9)
+        .unwrap()
+        .and_hms_opt(2, 30, 0)
+        .unwrap();
+    assert_eq!(new_york.resolve(skipped), None);
+    assert_eq!(Timezone::Utc.now().offset().local_minus_utc(), 0);
+}
+
+#[test]
+fn times_of_day_and_days_are_read_in_the_clock_timezone() {
+    let tokyo: Timezone = "Asia/Tokyo".parse().unwrap();
+    // Wednesday 2025-03-12 15:45 UTC is Thursday 00:45 in Tokyo.
+    let now = tokyo.convert(clock().0);
+    struct TokyoClock(DateTime<chrono::FixedOffset>, Timezone);
+    impl Clock for TokyoClock {
+        fn now(&self) -> DateTime<chrono::FixedOffset> {
+            self.0
+        }
+        fn timezone(&self) -> Timezone {
+            self.1
+        }
+    }
+    let clock = TokyoClock(now, tokyo);
+    let point = parse_time_point("today 09:00", "%Y-%m-%d %H:%M", &clock).unwrap();
+    assert_eq!(
+        point.start(tokyo).unwrap().to_rfc3339(),
+        "2025-03-13T09:00:00+09:00"
+    );
+    let point = parse_time_point("2025-03-13 09:00", "%Y-%m-%d %H:%M", &clock).unwrap();
+    assert_eq!(
+        point.start(tokyo).unwrap().to_rfc3339(),
+        "2025-03-13T09:00:00+09:00"
+    );
+    assert_eq!(
+        day(2025, 3, 13).start(tokyo).unwrap().to_rfc3339(),
+        "2025-03-13T00:00:00+09:00"
+    );
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::io;
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tempfile::TempDir;
+
+use nt::notes::{Durability, NoteParser, read_records_from_file};
+use nt::store::{FileStore, LockGuard, MemoryStore, NoteStore, Records, StoreLock};
+use nt::trash::{move_records_to_trash, restore_last_trashed, trash_file_path};
+
+const NOTES: &str = "2025-03-06 17:00 a\n2025-03-07 09:00 b\n\tb continued\n2025-03-07 16:30 c\n2025-03-08 10:00 d\n";
+
+#[test]
+fn trash_file_sits_next_to_note_file() {
+    let path = std::path::Path::new("/home/u/notes/daybook.txt");
+    assert_eq!(
+        trash_file_path(path),
+        std::path::PathBuf::from("/home/u/notes/daybook.txt.trash")
+    );
+}
+
+#[test]
+fn removed_records_are_restored_in_place_batch_by_batch() {
+    let tmp = TempDir::new().unwrap();
+    let notes = tmp.path().join("daybook.txt");
+    fs::write(&notes, NOTES).unwrap();
+    let store = FileStore::new(
+        notes.clone(),
+        NoteParser::new("%Y-%m-%d %H:%M"),
+        Durability::Flush,
+    );
+    let trash = trash_file_path(&notes);
+
+    let mut records = read_records_from_file(&notes).unwrap();
+    let removed = move_records_to_trash(&store, &trash, &mut records, &[2, 1]).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert_eq!(removed[0].record, "2025-03-07 09:00 b\nb continued");
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "2025-03-06 17:00 a\n2025-03-08 10:00 d\n"
+    );
+
+    let mut records = read_records_from_file(&notes).unwrap();
+    move_records_to_trash(&store, &trash, &mut records, &[1]).unwrap();
+    assert_eq!(fs::read_to_string(&notes).unwrap(), "2025-03-06 17:00 a\n");
+
+    let restored = restore_last_trashed(&store, &trash).unwrap().unwrap();
+    assert_eq!(restored.len(), 1);
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "2025-03-06 17:00 a\n2025-03-08 10:00 d\n"
+    );
+
+    restore_last_trashed(&store, &trash).unwrap().unwrap();
+    assert_eq!(fs::read_to_string(&notes).unwrap(), NOTES);
+    assert!(!trash_file_path(&notes).exists(), "empty trash is removed");
+    assert!(restore_last_trashed(&store, &trash).unwrap().is_none());
+}
+
+/// A store whose lock reports a failed commit on release.
+struct FailingCommitStore(MemoryStore);
+
+struct FailingCommit;
+
+impl LockGuard for FailingCommit {
+    fn release(self: Box<Self>) -> io::Result<()> {
+        Err(io::Error::other("commit failed"))
+    }
+}
+
+impl NoteStore for FailingCommitStore {
+    fn parser(&self) -> &NoteParser {
+        self.0.parser()
+    }
+    fn append(&self, record: &str) -> io::Result<()> {
+        self.0.append(record)
+    }
+    fn records(&self) -> io::Result<Option<Records<'_>>> {
+        self.0.records()
+    }
+    fn records_rev(&self) -> io::Result<Option<Records<'_>>> {
+        self.0.records_rev()
+    }
+    fn rewrite(&self, records: &[String]) -> io::Result<()> {
+        self.0.rewrite(records)
+    }
+    fn lock(&self) -> io::Result<StoreLock<'_>> {
+        Ok(StoreLock::with_guard(FailingCommit))
+    }
+}
+
+#[test]
+fn restore_keeps_the_trash_when_the_store_commit_fails() {
+    let tmp = TempDir::new().unwrap();
+    let trash = trash_file_path(&tmp.path().join("daybook.txt"));
+    let parser = NoteParser::new("%Y-%m-%d %H:%M");
+    let records: Vec<String> = ["2025-03-06 17:00 a", "2025-03-07 09:00 b"]
+        .map(String::from)
+        .to_vec();
+    let store = MemoryStore::with_records(parser.clone(), records.clone());
+    move_records_to_trash(&store, &trash, &mut records.clone(), &[1]).unwrap();
+    let before = fs::read_to_string(&trash).unwrap();
+
+    let failing = FailingCommitStore(MemoryStore::with_records(parser, vec![records[0].clone()]));
+    assert!(restore_last_trashed(&failing, &trash).is_err());
+    assert_eq!(fs::read_to_string(&trash).unwrap(), before);
+}
+
+/// A file store whose first lock release starts an `nt rm` of the first note on another
+/// thread and gives it time to run before returning.
+struct RacingStore {
+    store: FileStore,
+    trash: std::path::PathBuf,
+    rm: Mutex<Option<JoinHandle<()>>>,
+}
+
+struct StartRmOnRelease<'a> {
+    racing: &'a RacingStore,
+    lock: StoreLock<'a>,
+}
+
+impl LockGuard for StartRmOnRelease<'_> {
+    fn release(self: Box<Self>) -> io::Result<()> {
+        self.lock.release()?;
+        let mut rm = self.racing.rm.lock().unwrap();
+        if rm.is_none() {
+            let (store, trash) = (self.racing.store.clone(), self.racing.trash.clone());
+            *rm = Some(thread::spawn(move || {
+                let lock = store.lock().unwrap();
+                let mut records = store.read_records().unwrap().unwrap();
+                move_records_to_trash(&store, &trash, &mut records, &[0]).unwrap();
+                lock.release().unwrap();
+            }));
+            thread::sleep(Duration::from_millis(200));
+        }
+        Ok(())
+    }
+}
+
+impl NoteStore for RacingStore {
+    fn parser(&self) -> &NoteParser {
+        self.store.parser()
+    }
+    fn append(&self, record: &str) -> io::Result<()> {
+        self.store.append(record)
+    }
+    fn records(&self) -> io::Result<Option<Records<'_>>> {
+        self.store.records()
+    }
+    fn records_rev(&self) -> io::Result<Option<Records<'_>>> {
+        self.store.records_rev()
+    }
+    fn rewrite(&self, records: &[String]) -> io::Result<()> {
+        self.store.rewrite(records)
+    }
+    fn lock(&self) -> io::Result<StoreLock<'_>> {
+        Ok(StoreLock::with_guard(StartRmOnRelease {
+            racing: self,
+            lock: self.store.lock()?,
+        }))
+    }
+}
+
+#[test]
+fn removal_racing_an_undo_keeps_its_batch_in_the_trash() {
+    let tmp = TempDir::new().unwrap();
+    let notes = tmp.path().join("daybook.txt");
+    fs::write(&notes, NOTES).unwrap();
+    let store = FileStore::new(
+        notes.clone(),
+        NoteParser::new("%Y-%m-%d %H:%M"),
+        Durability::Flush,
+    );
+    let trash = trash_file_path(&notes);
+    let mut records = read_records_from_file(&notes).unwrap();
+    move_records_to_trash(&store, &trash, &mut records, &[3]).unwrap();
+
+    let racing = RacingStore {
+        store,
+        trash: trash.clone(),
+        rm: Mutex::new(None),
+    };
+    restore_last_trashed(&racing, &trash).unwrap().unwrap();
+    racing.rm.lock().unwrap().take().unwrap().join().unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "2025-03-07 09:00 b\n\tb continued\n2025-03-07 16:30 c\n2025-03-08 10:00 d\n"
+    );
+    let restored = restore_last_trashed(&racing.store, &trash)
+        .unwrap()
+        .unwrap();
+    assert_eq!(restored[0].record, "2025-03-06 17:00 a");
+    assert_eq!(fs::read_to_string(&notes).unwrap(), NOTES);
+}